@@ -1,15 +1,24 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use std::env;
+use std::fmt::Write;
+use std::io::Cursor;
 use std::path::PathBuf;
+use time::{macros::datetime, Duration, PrimitiveDateTime};
 
-use lib::{now, summarize_file};
+use lib::{now, summarize_file, summarize_reader, Options};
+
+const SYNTHETIC_DAYS: i64 = 2_000;
+const SESSIONS_PER_DAY: i64 = 4;
 
 pub fn summarize_lines_bench(c: &mut Criterion) {
     let mut path = PathBuf::new();
     path.push(env::var("CARGO_MANIFEST_DIR").unwrap());
     path.push("benches");
-    path.set_file_name("anonimized-timelog");
-    path.set_extension("txt");
+    path.push("anonimized-timelog.txt");
+    if !path.is_file() {
+        eprintln!("skipping summarize_lines, {} is missing", path.display());
+        return;
+    }
     let now = now().unwrap();
 
     c.bench_with_input(
@@ -18,11 +27,49 @@ pub fn summarize_lines_bench(c: &mut Criterion) {
         |b, s| {
             b.iter(|| {
                 let (path, now) = s;
-                summarize_file(path, *now)
+                summarize_file(path, now, &Options::default())
             })
         },
     );
 }
 
-criterion_group!(benches, summarize_lines_bench);
+/// Generates a timelog of `SYNTHETIC_DAYS` days of `SESSIONS_PER_DAY` hourly
+/// hour sessions, cycling through `accounts` distinct accounts.
+fn synthetic_timelog(accounts: usize) -> String {
+    let start = datetime!(2015 - 01 - 01 08:00:00);
+    let format =
+        time::macros::format_description!("[year]/[month]/[day] [hour repr:24]:[minute]:[second]");
+    let mut log = String::new();
+    for i in 0..SYNTHETIC_DAYS * SESSIONS_PER_DAY {
+        let clockin: PrimitiveDateTime =
+            start + Duration::days(i / SESSIONS_PER_DAY) + Duration::hours(i % SESSIONS_PER_DAY);
+        let clockout = clockin + Duration::minutes(55);
+        let account = i as usize % accounts;
+        let _ = writeln!(
+            log,
+            "i {} client{}:project{}",
+            clockin.format(format).unwrap(),
+            account % 20,
+            account
+        );
+        let _ = writeln!(log, "o {}", clockout.format(format).unwrap());
+    }
+    log
+}
+
+/// Compares a log with a single account, which only aggregates, to one
+/// with many distinct accounts, which exercises the per-project maps.
+pub fn summarize_projects_bench(c: &mut Criterion) {
+    let now = datetime!(2022 - 01 - 01 12:00:00);
+    let mut group = c.benchmark_group("summarize_reader");
+    for (name, accounts) in [("aggregate-only", 1), ("per-project", 2_000)] {
+        let log = synthetic_timelog(accounts);
+        group.bench_with_input(BenchmarkId::new("synthetic", name), &log, |b, log| {
+            b.iter(|| summarize_reader(Cursor::new(log.as_bytes()), &now, &Options::default()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, summarize_lines_bench, summarize_projects_bench);
 criterion_main!(benches);
@@ -0,0 +1,1275 @@
+use crate::report::{self, DEFAULT_WIDTH};
+use anyhow::{anyhow, bail, Context, Result};
+use lib::{
+    from_epoch, parse_date, parse_hour_minute, parse_month, parse_timestamp, parse_utc_offset,
+    NegativeStyle, Options,
+};
+use regex::Regex;
+use std::{path::PathBuf, str::FromStr};
+use time::{Duration, Month, PrimitiveDateTime, Time, UtcOffset};
+use tz::TimeZone;
+
+const DEFAULT_REMINDER_WINDOW: Duration = Duration::minutes(15);
+const MIN_WIDTH: usize = 20;
+const DEFAULT_INTERVAL: Duration = Duration::minutes(1);
+
+pub const USAGE: &str = r"usage: tlsum [status] [options]
+
+commands:
+    status                  print only whether clocked in and, when so, the
+                            account, the clock in and the time elapsed
+
+options:
+    --timelog <path>        the timelog to summarize, overrides $TIMELOG
+    --timelog-dir <dir>     summarize the files named timelog-* in this
+                            directory together, in order of their names
+    --target-hours <hours>  the hours to work per day, defaults to 8
+    --schedule <file>       take the hours to work per day from the file, a
+                            line like 2022-06-01,6 per date they change on
+    --contract-hours <hours>
+                            the contractual hours per day for the fixed
+                            still to work and time to leave, defaults to
+                            the target hours
+    --exempt-first-day      expect one day less than worked (default)
+    --no-exempt-first-day   expect every worked day
+    --cap-daily             count no day for more than the target hours,
+                            printing the excess as uncounted overtime
+    --overtime-through <day>
+                            today or yesterday, whether the cummulative
+                            overtime includes today, defaults to yesterday
+    --first-day-overtime    hold a single worked day before today against
+                            the target hours instead of exempting it
+    -v, --verbose           print every parsed interval to stderr
+    --in-marker <token>     the token starting a clock in line, defaults to i
+    --out-marker <token>    the token starting a clock out line, defaults to o
+    --comment-chars <chars> the characters starting a comment, defaults to #;
+                            and $TLSUM_COMMENT_CHARS, none when empty
+    --lenient               skip lines that cannot be used, with a warning
+    --strict                fail on a file of --timelog-dir that cannot be
+                            read or has a malformed line, or a timestamp
+                            after now, instead of a warning
+    --as-of-yesterday       summarize as if it were the end of yesterday
+    --today <YYYY-MM-DD>    report the figures of this date as today's
+    --no-open-session       leave out a trailing clock in without clock out
+    --merge-adjacent        count an interval starting at the end of the
+                            previous one as the same session
+    --trust-clockout-account
+                            attribute a clock in without an account to the
+                            account on its clock out line
+    --skip-leading-out      ignore clock outs before the first clock in
+    --resume-latest         on a clock in while clocked in, discard the
+                            earlier clock in with a warning
+    --assume-clockout <HH:MM>
+                            clock out a trailing clock in of a past day at
+                            this time of that day
+    --lunch <minutes>       add a lunch break to the time to leave, until
+                            a break longer than 20 minutes has been taken
+    --min-day <minutes>     leave past days with less time worked out of the
+                            number of days worked and the average, their
+                            time still counts in the total and the overtime
+    --round <minutes>       round the duration of every interval to a
+                            multiple of minutes
+    --round-mode <mode>     up, down, nearest or bankers, defaults to nearest
+    --account <glob>        only include matching accounts, repeatable
+    --exclude-account <glob>
+                            exclude matching accounts, applied after
+                            --account, repeatable
+    --owner <name>          only include accounts ending in this name, like
+                            fred in e:fc:fred
+    --billable <glob>       count matching accounts as billable, others as
+                            non-billable, repeatable
+    --no-reminders          do not warn when nothing was clocked today
+    --reminder <path>       write today's date to path once, when clocked in
+                            and the time still to work is within the window
+    --reminder-window <minutes>
+                            the reminder window, defaults to 15 minutes
+    --alias <old=new>       count account old as account new, repeatable
+    --strip-prefix <s>      remove a leading segment like e:fc: from accounts,
+                            repeatable, the first matching one is removed
+    --days                  print the first punch in and time worked per day
+    --years                 print the time worked per year
+    --fiscal-year-start <month>
+                            start the years of --years in this month, as a
+                            number or a name, defaults to january
+    --part-of-day           print the time worked in the morning, before
+                            12:00, the afternoon and the evening, from 18:00
+    --projects              print the time worked and the date last worked
+                            per account
+    --group-by <regex>      print the time worked per group of accounts, the
+                            group being the first capture of the regex
+    --sections <names>      print only these lines of the summary, in this
+                            order, e.g. first-in,worked-today,leave
+    --streaks               print the current and the longest streak of
+                            consecutive worked days
+    --streak-skip-weekends  do not let a weekend without work break a streak
+    --expected-start <HH:MM>
+                            print the number of days started on time and late
+    --start-grace <minutes> count a start this long after --expected-start as
+                            on time, defaults to 0
+    --plan-leave <HH:MM>    print the time worked, overtime and balance when
+                            clocking out at this time today
+    --holidays <file>       print the number of weekend days and of the
+                            holidays in the file, one YYYY-MM-DD per line,
+                            worked
+    --break-after <hours>   print the time until the open session has lasted
+                            this long, to take a break
+    --legal-max <hours>     fail when a day has more hours worked than this,
+                            listing those days
+    --legal-max-warn        only warn about the days over --legal-max
+    --explain               print how the overtime and the time still to
+                            work are derived
+    --recompute-target <hours>
+                            also print the overtime had the target hours
+                            always been this
+    --compact               print durations tersely, like 8h 0m
+    --with-seconds          print durations with their seconds
+    --group-digits          group the digits of the hours per thousand
+    --group-separator <char>
+                            the separator used by --group-digits, defaults to ,
+    --decimal-sep <.|,>     the decimal separator of the CSV exports, a comma
+                            switching their delimiter to ;
+    --neg-style <minus|paren>
+                            mark negative durations with a minus, the
+                            default, or parentheses
+    --in-days               also express the total and overtime in workdays
+                            of the target hours
+    --watch                 re-read the timelog and re-print the summary every
+                            interval until interrupted
+    --interval <seconds>    the interval for --watch, defaults to 60
+    --progress              show the percentage of the timelog read on stderr
+    --width <n>             the width of the table, defaults to 71
+    --dst-aware <time zone> count the elapsed time of intervals in the time
+                            zone, e.g. Europe/Amsterdam, across daylight
+                            saving time transitions
+    --tz <offset>           compute now at this offset from UTC, e.g. +02:00,
+                            instead of the local offset
+    --now <timestamp>       summarize at this local time instead of now,
+                            e.g. 2022/04/22 17:00:00 as a single argument
+    --epoch-now <seconds>   summarize at this Unix time instead of now
+    --text                  print the summary, the default, instead of the
+                            output_format of .tlsumrc or an earlier --json,
+                            --oneline, --timesheet or --balance-chart
+    --oneline               print a single line for a status bar instead of
+                            the summary
+    --from-last-clockin     print only the time elapsed since the clock in
+                            when clocked in, else clocked out with a
+                            non-zero exit code
+    --timesheet             print a weekly timesheet of this week, with the
+                            first clock in, last clock out and time worked
+                            per day, instead of the summary
+    --balance-chart         print every worked day with its deviation from the
+                            target and the running total instead of the
+                            summary
+    --matrix                print the hours per date and top-level account
+                            as CSV instead of the summary
+    --hour-histogram        print the minutes worked per hour of the day as
+                            CSV instead of the summary
+    --bill-round-up <hours> print the hours per date as CSV, next to them
+                            rounded up to a multiple of the hours, e.g. 0.25h,
+                            for invoicing, instead of the summary
+    --dump-intervals        print every interval as a tab separated line of
+                            start, end, seconds and account instead of the
+                            summary
+    --hledger               print every interval as an hledger transaction
+                            posting its hours instead of the summary
+    --json-lines            print every interval as a line of JSON as soon
+                            as it is read instead of the summary
+    --json                  print the summary as JSON, a failure as a JSON
+                            error object
+    --output-tz <offset>    append this offset from UTC, e.g. +02:00, to the
+                            timestamps of --json and --json-lines
+    --baseline <path>       print the changes in overtime and total since the
+                            summary saved with --json at path
+    --out <path>            write the output to path, replacing it at once so
+                            a reader never sees a partial write, instead of
+                            printing it
+    --tee                   also print the output written with --out";
+
+/// The command line arguments `tlsum` was invoked with.
+#[derive(Debug)]
+pub struct Args {
+    pub timelog: Option<PathBuf>,
+    /// The directory of monthly timelogs to summarize instead of `timelog`.
+    pub timelog_dir: Option<PathBuf>,
+    pub options: Options,
+    pub reminder: Option<PathBuf>,
+    pub reminder_window: Duration,
+    pub projects: bool,
+    pub days: bool,
+    pub years: bool,
+    pub part_of_day: bool,
+    /// The first month of the years of `--years`.
+    pub fiscal_year_start: Month,
+    /// The pattern extracting the group from an account for `--group-by`.
+    pub group_by: Option<Regex>,
+    /// The sections of the summary table to print, in this order.
+    pub sections: Option<Vec<String>>,
+    pub expected_start: Option<Time>,
+    /// How long after `expected_start` a start still counts as on time.
+    pub start_grace: Duration,
+    /// A planned clock out today to print the resulting figures of.
+    pub plan_leave: Option<Time>,
+    /// A file of holidays, one `YYYY-MM-DD` per line, to count the worked
+    /// ones of next to the worked weekend days.
+    pub holidays: Option<PathBuf>,
+    /// A file of target hours per effective date, read into
+    /// `options.schedule` once at startup.
+    pub schedule: Option<PathBuf>,
+    pub streaks: bool,
+    pub explain: bool,
+    /// The length of a session after which `--break-after` recommends a
+    /// break.
+    pub break_after: Option<Duration>,
+    /// The most hours that may be worked on a day for `--legal-max`.
+    pub legal_max: Option<Duration>,
+    pub legal_max_warn: bool,
+    /// An alternative target to print the overtime at for `--recompute-target`.
+    pub recompute_target: Option<Duration>,
+    pub compact: bool,
+    pub with_seconds: bool,
+    pub group_digits: bool,
+    pub in_days: bool,
+    pub group_separator: char,
+    /// The decimal separator of the CSV exports, `.` or `,`.
+    pub decimal_separator: char,
+    pub negative_style: NegativeStyle,
+    pub matrix: bool,
+    pub oneline: bool,
+    pub from_last_clockin: bool,
+    pub balance_chart: bool,
+    pub timesheet: bool,
+    pub dump_intervals: bool,
+    pub json_lines: bool,
+    pub hledger: bool,
+    pub hour_histogram: bool,
+    /// The increment to round each day up to for `--bill-round-up`.
+    pub bill_round_up: Option<Duration>,
+    pub json: bool,
+    /// A summary saved with `--json` to print the changes since.
+    pub baseline: Option<PathBuf>,
+    /// A file to write the output to instead of stdout.
+    pub out: Option<PathBuf>,
+    /// Also print the output on stdout when writing it to `out`.
+    pub tee: bool,
+    pub as_of_yesterday: bool,
+    pub no_reminders: bool,
+    pub width: usize,
+    pub watch: bool,
+    /// Set by the `status` command.
+    pub status: bool,
+    pub progress: bool,
+    pub interval: Duration,
+    /// The offset from UTC to compute now at instead of the local one.
+    pub tz: Option<UtcOffset>,
+    /// The offset appended to the timestamps of `--json` and `--json-lines`.
+    pub output_tz: Option<UtcOffset>,
+    /// The moment to summarize at instead of the current time, from `--now`
+    /// or `--epoch-now`.
+    pub now: Option<PrimitiveDateTime>,
+    /// Whether `exclude_accounts` holds the accounts of `.tlsumrc`, which the
+    /// first `--exclude-account` replaces.
+    pub configured_excludes: bool,
+    /// Whether the output format was set by `.tlsumrc`, which any of the
+    /// output flags replaces.
+    pub configured_output: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            timelog: None,
+            timelog_dir: None,
+            options: Options::default(),
+            reminder: None,
+            reminder_window: DEFAULT_REMINDER_WINDOW,
+            projects: false,
+            days: false,
+            years: false,
+            part_of_day: false,
+            fiscal_year_start: Month::January,
+            group_by: None,
+            sections: None,
+            expected_start: None,
+            start_grace: Duration::ZERO,
+            plan_leave: None,
+            holidays: None,
+            schedule: None,
+            streaks: false,
+            explain: false,
+            legal_max: None,
+            break_after: None,
+            legal_max_warn: false,
+            recompute_target: None,
+            compact: false,
+            with_seconds: false,
+            group_digits: false,
+            in_days: false,
+            group_separator: ',',
+            decimal_separator: '.',
+            negative_style: NegativeStyle::default(),
+            matrix: false,
+            oneline: false,
+            from_last_clockin: false,
+            balance_chart: false,
+            timesheet: false,
+            dump_intervals: false,
+            json_lines: false,
+            hledger: false,
+            hour_histogram: false,
+            bill_round_up: None,
+            json: false,
+            baseline: None,
+            out: None,
+            tee: false,
+            as_of_yesterday: false,
+            no_reminders: false,
+            width: DEFAULT_WIDTH,
+            watch: false,
+            status: false,
+            progress: false,
+            interval: DEFAULT_INTERVAL,
+            tz: None,
+            output_tz: None,
+            now: None,
+            configured_excludes: false,
+            configured_output: false,
+        }
+    }
+}
+
+impl Args {
+    /// Parses the given arguments, not including the program name.
+    #[cfg(test)]
+    pub fn parse<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        Self::default().update(args)
+    }
+
+    /// Drops the output format set by `.tlsumrc`, if any, before an output
+    /// flag, or a section of the summary, asks for another one.
+    fn replace_configured_output(&mut self) {
+        if std::mem::take(&mut self.configured_output) {
+            self.clear_output_format();
+        }
+    }
+
+    /// Unsets the output formats `output_format` of `.tlsumrc` may set.
+    fn clear_output_format(&mut self) {
+        self.json = false;
+        self.oneline = false;
+        self.timesheet = false;
+        self.balance_chart = false;
+    }
+
+    /// Overrides the arguments with the given ones, not including the
+    /// program name.
+    pub fn update<I>(self, args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = self;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "status" => {
+                    parsed.replace_configured_output();
+                    parsed.status = true;
+                }
+                "--timelog" => parsed.timelog = Some(value(&mut args, &arg)?.into()),
+                "--timelog-dir" => parsed.timelog_dir = Some(value(&mut args, &arg)?.into()),
+                "--contract-hours" => {
+                    parsed.options.contract = Some(parse_hours(&value(&mut args, &arg)?)?);
+                }
+                "--target-hours" => {
+                    parsed.options.target = parse_hours(&value(&mut args, &arg)?)?;
+                }
+                "--exempt-first-day" => parsed.options.exempt_first_day = true,
+                "--no-exempt-first-day" => parsed.options.exempt_first_day = false,
+                "--first-day-overtime" => parsed.options.first_day_overtime = true,
+                "--overtime-through" => {
+                    parsed.options.overtime_through = value(&mut args, &arg)?.parse()?;
+                }
+                "--cap-daily" => parsed.options.cap_daily = true,
+                "-v" | "--verbose" => parsed.options.verbose = true,
+                "--lenient" => parsed.options.lenient = true,
+                "--strict" => parsed.options.strict = true,
+                "--as-of-yesterday" => parsed.as_of_yesterday = true,
+                "--today" => parsed.options.today = Some(parse_date(&value(&mut args, &arg)?)?),
+                "--no-open-session" => parsed.options.no_open_session = true,
+                "--merge-adjacent" => parsed.options.merge_adjacent = true,
+                "--trust-clockout-account" => parsed.options.trust_clockout_account = true,
+                "--skip-leading-out" => parsed.options.skip_leading_out = true,
+                "--resume-latest" => parsed.options.resume_latest = true,
+                "--in-marker" => parsed.options.in_marker = parse_marker(&mut args, &arg)?,
+                "--out-marker" => parsed.options.out_marker = parse_marker(&mut args, &arg)?,
+                "--comment-chars" => {
+                    parsed.options.comment_chars = value(&mut args, &arg)?.chars().collect();
+                }
+                "--assume-clockout" => {
+                    parsed.options.assume_clockout =
+                        Some(parse_hour_minute(&value(&mut args, &arg)?)?);
+                }
+                "--lunch" => {
+                    parsed.options.lunch = Some(parse_minutes(&mut args, &arg)?);
+                }
+                "--min-day" => {
+                    parsed.options.min_day = Some(parse_minutes(&mut args, &arg)?);
+                }
+                "--round" => {
+                    let minutes: u32 = parse_value(&mut args, &arg)?;
+                    if minutes == 0 {
+                        bail!("expected a granularity of at least one minute");
+                    }
+                    parsed.options.round = Some(Duration::minutes(minutes.into()));
+                }
+                "--round-mode" => parsed.options.round_mode = value(&mut args, &arg)?.parse()?,
+                "--account" => parsed.options.accounts.push(value(&mut args, &arg)?),
+                "--exclude-account" => {
+                    if std::mem::take(&mut parsed.configured_excludes) {
+                        parsed.options.exclude_accounts.clear();
+                    }
+                    parsed
+                        .options
+                        .exclude_accounts
+                        .push(value(&mut args, &arg)?);
+                }
+                "--owner" => parsed.options.owner = Some(value(&mut args, &arg)?),
+                "--billable" => parsed.options.billable.push(value(&mut args, &arg)?),
+                "--no-reminders" => parsed.no_reminders = true,
+                "--reminder" => parsed.reminder = Some(value(&mut args, &arg)?.into()),
+                "--reminder-window" => {
+                    parsed.reminder_window = parse_minutes(&mut args, &arg)?;
+                }
+                "--alias" => {
+                    let alias = value(&mut args, &arg)?;
+                    let (old, new) = alias
+                        .split_once('=')
+                        .with_context(|| format!("expected old=new, got: [{}]", alias))?;
+                    parsed
+                        .options
+                        .aliases
+                        .push((old.to_owned(), new.to_owned()));
+                }
+                "--strip-prefix" => parsed.options.strip_prefixes.push(value(&mut args, &arg)?),
+                "--projects" => {
+                    parsed.replace_configured_output();
+                    parsed.projects = true;
+                }
+                "--days" => {
+                    parsed.replace_configured_output();
+                    parsed.days = true;
+                }
+                "--years" => {
+                    parsed.replace_configured_output();
+                    parsed.years = true;
+                }
+                "--part-of-day" => {
+                    parsed.replace_configured_output();
+                    parsed.part_of_day = true;
+                }
+                "--fiscal-year-start" => {
+                    parsed.fiscal_year_start = parse_month(&value(&mut args, &arg)?)?;
+                }
+                "--group-by" => {
+                    let pattern = value(&mut args, &arg)?;
+                    parsed.group_by = Some(
+                        Regex::new(&pattern)
+                            .with_context(|| format!("invalid pattern for [{}]", arg))?,
+                    );
+                }
+                "--sections" => {
+                    parsed.sections = Some(parse_sections(&value(&mut args, &arg)?)?);
+                }
+                "--streaks" => parsed.streaks = true,
+                "--streak-skip-weekends" => parsed.options.streak_skips_weekends = true,
+                "--holidays" => parsed.holidays = Some(value(&mut args, &arg)?.into()),
+                "--schedule" => parsed.schedule = Some(value(&mut args, &arg)?.into()),
+                "--expected-start" => {
+                    parsed.expected_start = Some(parse_hour_minute(&value(&mut args, &arg)?)?);
+                }
+                "--start-grace" => {
+                    parsed.start_grace = parse_minutes(&mut args, &arg)?;
+                }
+                "--neg-style" => parsed.negative_style = value(&mut args, &arg)?.parse()?,
+                "--break-after" => {
+                    parsed.break_after = Some(parse_hours(&value(&mut args, &arg)?)?);
+                }
+                "--legal-max" => parsed.legal_max = Some(parse_hours(&value(&mut args, &arg)?)?),
+                "--legal-max-warn" => parsed.legal_max_warn = true,
+                "--explain" => {
+                    parsed.replace_configured_output();
+                    parsed.explain = true;
+                }
+                "--recompute-target" => {
+                    parsed.recompute_target = Some(parse_hours(&value(&mut args, &arg)?)?);
+                }
+                "--plan-leave" => {
+                    parsed.plan_leave = Some(parse_hour_minute(&value(&mut args, &arg)?)?);
+                }
+                "--in-days" => parsed.in_days = true,
+                "--compact" => parsed.compact = true,
+                "--with-seconds" => parsed.with_seconds = true,
+                "--group-digits" => parsed.group_digits = true,
+                "--group-separator" => parsed.group_separator = parse_value(&mut args, &arg)?,
+                "--decimal-sep" => {
+                    parsed.decimal_separator = match value(&mut args, &arg)?.as_str() {
+                        "." => '.',
+                        "," => ',',
+                        other => bail!("unknown decimal separator: [{}], expected . or ,", other),
+                    };
+                }
+                "--watch" => parsed.watch = true,
+                "--progress" => parsed.progress = true,
+                "--interval" => {
+                    let seconds: u32 = parse_value(&mut args, &arg)?;
+                    if seconds == 0 {
+                        bail!("expected an interval of at least one second");
+                    }
+                    parsed.interval = Duration::seconds(seconds.into());
+                }
+                "--width" => {
+                    parsed.width = parse_value(&mut args, &arg)?;
+                    if parsed.width < MIN_WIDTH {
+                        bail!("expected a width of at least {}", MIN_WIDTH);
+                    }
+                }
+                "--dst-aware" => {
+                    let zone = value(&mut args, &arg)?;
+                    parsed.options.time_zone = Some(
+                        TimeZone::from_posix_tz(&zone)
+                            .with_context(|| format!("unknown time zone: [{}]", zone))?,
+                    );
+                }
+                "--tz" => parsed.tz = Some(parse_utc_offset(&value(&mut args, &arg)?)?),
+                "--output-tz" => {
+                    parsed.output_tz = Some(parse_utc_offset(&value(&mut args, &arg)?)?);
+                }
+                "--now" | "--epoch-now" if parsed.now.is_some() => {
+                    bail!("only one of [--now] and [--epoch-now] may be given");
+                }
+                "--now" => parsed.now = Some(parse_timestamp(&value(&mut args, &arg)?)?),
+                "--epoch-now" => {
+                    let seconds = parse_value(&mut args, &arg)?;
+                    parsed.now = Some(
+                        from_epoch(seconds)
+                            .with_context(|| format!("invalid value for [{}]", arg))?,
+                    );
+                }
+                "--matrix" => {
+                    parsed.replace_configured_output();
+                    parsed.matrix = true;
+                }
+                "--oneline" => {
+                    parsed.replace_configured_output();
+                    parsed.oneline = true;
+                }
+                "--from-last-clockin" => {
+                    parsed.replace_configured_output();
+                    parsed.from_last_clockin = true;
+                }
+                "--balance-chart" => {
+                    parsed.replace_configured_output();
+                    parsed.balance_chart = true;
+                }
+                "--timesheet" => {
+                    parsed.replace_configured_output();
+                    parsed.timesheet = true;
+                }
+                "--dump-intervals" => {
+                    parsed.replace_configured_output();
+                    parsed.dump_intervals = true;
+                }
+                "--json-lines" => {
+                    parsed.replace_configured_output();
+                    parsed.json_lines = true;
+                }
+                "--hledger" => {
+                    parsed.replace_configured_output();
+                    parsed.hledger = true;
+                }
+                "--hour-histogram" => {
+                    parsed.replace_configured_output();
+                    parsed.hour_histogram = true;
+                }
+                "--bill-round-up" => {
+                    parsed.replace_configured_output();
+                    let increment = value(&mut args, &arg)?;
+                    parsed.bill_round_up = Some(parse_hours(
+                        increment.strip_suffix('h').unwrap_or(&increment),
+                    )?);
+                }
+                "--json" => {
+                    parsed.replace_configured_output();
+                    parsed.json = true;
+                }
+                "--text" => {
+                    parsed.configured_output = false;
+                    parsed.clear_output_format();
+                }
+                "--baseline" => parsed.baseline = Some(value(&mut args, &arg)?.into()),
+                "--out" => parsed.out = Some(value(&mut args, &arg)?.into()),
+                "--tee" => parsed.tee = true,
+                other => bail!("unknown argument: [{}]\n{}", other, USAGE),
+            }
+        }
+        if parsed.options.in_marker == parsed.options.out_marker {
+            bail!(
+                "expected different markers for [--in-marker] and [--out-marker], got: [{}]",
+                parsed.options.in_marker
+            );
+        }
+        Ok(parsed)
+    }
+}
+
+/// Parses a positive, possibly fractional, number of hours.
+pub fn parse_hours(hours: &str) -> Result<Duration> {
+    let parsed: f64 = hours
+        .parse()
+        .with_context(|| format!("expected a number of hours, got: [{}]", hours))?;
+    if !parsed.is_finite() || parsed <= 0.0 || parsed > 24.0 {
+        bail!("expected a number of hours in (0, 24], got: [{}]", hours);
+    }
+    Ok(Duration::seconds_f64(parsed * 3600.0))
+}
+
+fn value<I>(args: &mut I, name: &str) -> Result<String>
+where
+    I: Iterator<Item = String>,
+{
+    args.next()
+        .with_context(|| format!("missing value for [{}]\n{}", name, USAGE))
+}
+
+/// Parses a comma separated list of the names in [`report::SECTIONS`].
+fn parse_sections(sections: &str) -> Result<Vec<String>> {
+    sections
+        .split(',')
+        .map(|name| {
+            if report::SECTIONS.contains(&name) {
+                Ok(name.to_owned())
+            } else {
+                Err(anyhow!(
+                    "unknown section: [{}], expected one of {}",
+                    name,
+                    report::SECTIONS.join(", ")
+                ))
+            }
+        })
+        .collect()
+}
+
+fn parse_value<I, T>(args: &mut I, name: &str) -> Result<T>
+where
+    I: Iterator<Item = String>,
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    let value = value(args, name)?;
+    value
+        .parse()
+        .with_context(|| format!("invalid value for [{}]: [{}]", name, value))
+}
+
+/// Parses a clock marker, a token without whitespace that can start a line.
+fn parse_marker<I>(args: &mut I, name: &str) -> Result<String>
+where
+    I: Iterator<Item = String>,
+{
+    let marker = value(args, name)?;
+    if marker.is_empty() || marker.contains(char::is_whitespace) {
+        bail!(
+            "expected a marker without whitespace for [{}], got: [{}]",
+            name,
+            marker
+        );
+    }
+    Ok(marker)
+}
+
+/// Parses a whole, non-negative number of minutes.
+fn parse_minutes<I>(args: &mut I, name: &str) -> Result<Duration>
+where
+    I: Iterator<Item = String>,
+{
+    let minutes: u32 = parse_value(args, name)?;
+    Ok(Duration::minutes(minutes.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib::{OvertimeThrough, RoundMode};
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        Args::parse(args.iter().map(|arg| (*arg).to_owned()))
+    }
+
+    #[test]
+    fn should_default_to_exempt_first_day() {
+        let args = parse(&[]).unwrap();
+        assert!(args.options.exempt_first_day);
+    }
+
+    #[test]
+    fn should_parse_no_exempt_first_day() {
+        let args = parse(&["--no-exempt-first-day"]).unwrap();
+        assert!(!args.options.exempt_first_day);
+    }
+
+    #[test]
+    fn should_parse_cap_daily() {
+        assert!(!parse(&[]).unwrap().options.cap_daily);
+        assert!(parse(&["--cap-daily"]).unwrap().options.cap_daily);
+    }
+
+    #[test]
+    fn should_parse_first_day_overtime() {
+        assert!(!parse(&[]).unwrap().options.first_day_overtime);
+        let args = parse(&["--first-day-overtime"]).unwrap();
+        assert!(args.options.first_day_overtime);
+    }
+
+    #[test]
+    fn should_parse_overtime_through() {
+        let yesterday = parse(&[]).unwrap().options.overtime_through;
+        assert_eq!(yesterday, OvertimeThrough::Yesterday);
+        let args = parse(&["--overtime-through", "today"]).unwrap();
+        assert_eq!(args.options.overtime_through, OvertimeThrough::Today);
+        assert!(parse(&["--overtime-through", "tomorrow"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_timelog() {
+        let args = parse(&["--timelog", "timelog.txt"]).unwrap();
+        assert_eq!(args.timelog, Some(PathBuf::from("timelog.txt")));
+    }
+
+    #[test]
+    fn should_parse_timelog_dir() {
+        let args = parse(&["--timelog-dir", "logs", "--strict"]).unwrap();
+        assert_eq!(args.timelog_dir, Some(PathBuf::from("logs")));
+        assert!(args.options.strict);
+        assert!(!parse(&[]).unwrap().options.strict);
+    }
+
+    #[test]
+    fn should_parse_contract_hours() {
+        assert_eq!(parse(&[]).unwrap().options.contract, None);
+        let args = parse(&["--contract-hours", "8", "--target-hours", "7.5"]).unwrap();
+        assert_eq!(args.options.contract, Some(Duration::hours(8)));
+        assert_eq!(args.options.target, Duration::minutes(450));
+    }
+
+    #[test]
+    fn should_parse_target_hours() {
+        let args = parse(&["--target-hours", "7.5"]).unwrap();
+        assert_eq!(args.options.target, Duration::minutes(450));
+        assert!(parse(&["--target-hours", "0"]).is_err());
+        assert!(parse(&["--target-hours", "NaN"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_verbose() {
+        assert!(parse(&["-v"]).unwrap().options.verbose);
+        assert!(parse(&["--verbose"]).unwrap().options.verbose);
+    }
+
+    #[test]
+    fn should_parse_lunch_minutes() {
+        let args = parse(&["--lunch", "30"]).unwrap();
+        assert_eq!(args.options.lunch, Some(Duration::minutes(30)));
+    }
+
+    #[test]
+    fn should_parse_round() {
+        let args = parse(&["--round-mode", "bankers", "--round", "15"]).unwrap();
+        assert_eq!(args.options.round, Some(Duration::minutes(15)));
+        assert_eq!(args.options.round_mode, RoundMode::Bankers);
+        assert_eq!(parse(&[]).unwrap().options.round_mode, RoundMode::Nearest);
+        assert!(parse(&["--round", "0"]).is_err());
+        assert!(parse(&["--round-mode", "sideways"]).is_err());
+    }
+
+    #[test]
+    fn should_reject_missing_or_invalid_value() {
+        assert!(parse(&["--lunch"]).is_err());
+        assert!(parse(&["--lunch", "half an hour"]).is_err());
+        assert!(parse(&["--lunch", "-30"]).is_err());
+    }
+
+    #[test]
+    fn should_collect_repeated_account_patterns() {
+        let args = parse(&[
+            "--account",
+            "acme:*",
+            "--exclude-account",
+            "*:admin",
+            "--exclude-account",
+            "admin:*",
+        ])
+        .unwrap();
+        assert_eq!(args.options.accounts, vec!["acme:*"]);
+        assert_eq!(args.options.exclude_accounts, vec!["*:admin", "admin:*"]);
+    }
+
+    #[test]
+    fn should_parse_reminder() {
+        let args = parse(&["--reminder", "/tmp/leave"]).unwrap();
+        assert_eq!(args.reminder, Some(PathBuf::from("/tmp/leave")));
+        assert_eq!(args.reminder_window, DEFAULT_REMINDER_WINDOW);
+        let args = parse(&["--reminder-window", "5"]).unwrap();
+        assert_eq!(args.reminder_window, Duration::minutes(5));
+        assert!(parse(&["--reminder-window", "-5"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_strip_prefixes() {
+        let args = parse(&["--strip-prefix", "e:fc:", "--strip-prefix", "e:"]).unwrap();
+        assert_eq!(args.options.strip_prefixes, vec!["e:fc:", "e:"]);
+    }
+
+    #[test]
+    fn should_parse_owner() {
+        let args = parse(&["--owner", "fred"]).unwrap();
+        assert_eq!(args.options.owner.as_deref(), Some("fred"));
+    }
+
+    #[test]
+    fn should_parse_aliases() {
+        let args = parse(&["--alias", "acme=acme-corp", "--alias", "a=b=c"]).unwrap();
+        assert_eq!(
+            args.options.aliases,
+            vec![
+                ("acme".to_owned(), "acme-corp".to_owned()),
+                ("a".to_owned(), "b=c".to_owned())
+            ]
+        );
+        assert!(parse(&["--alias", "acme"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_holidays() {
+        assert_eq!(parse(&[]).unwrap().holidays, None);
+        let args = parse(&["--holidays", "holidays.txt"]).unwrap();
+        assert_eq!(args.holidays, Some(PathBuf::from("holidays.txt")));
+    }
+
+    #[test]
+    fn should_parse_schedule() {
+        assert_eq!(parse(&[]).unwrap().schedule, None);
+        let args = parse(&["--schedule", "schedule.csv"]).unwrap();
+        assert_eq!(args.schedule, Some(PathBuf::from("schedule.csv")));
+    }
+
+    #[test]
+    fn should_parse_streaks() {
+        let args = parse(&[]).unwrap();
+        assert!(!args.streaks && !args.options.streak_skips_weekends);
+        let args = parse(&["--streaks", "--streak-skip-weekends"]).unwrap();
+        assert!(args.streaks && args.options.streak_skips_weekends);
+    }
+
+    #[test]
+    fn should_parse_expected_start() {
+        let args = parse(&["--expected-start", "09:00"]).unwrap();
+        assert_eq!(args.expected_start, Some(time::macros::time!(09:00)));
+        assert!(parse(&["--expected-start", "nine"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_start_grace() {
+        assert_eq!(parse(&[]).unwrap().start_grace, Duration::ZERO);
+        let args = parse(&["--start-grace", "10"]).unwrap();
+        assert_eq!(args.start_grace, Duration::minutes(10));
+        assert!(parse(&["--start-grace", "ten"]).is_err());
+        assert!(parse(&["--start-grace", "-10"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_compact() {
+        assert!(!parse(&[]).unwrap().compact);
+        assert!(parse(&["--compact"]).unwrap().compact);
+    }
+
+    #[test]
+    fn should_parse_with_seconds() {
+        assert!(!parse(&[]).unwrap().with_seconds);
+        assert!(parse(&["--with-seconds"]).unwrap().with_seconds);
+    }
+
+    #[test]
+    fn should_parse_decimal_separator() {
+        assert_eq!(parse(&[]).unwrap().decimal_separator, '.');
+        assert_eq!(
+            parse(&["--decimal-sep", ","]).unwrap().decimal_separator,
+            ','
+        );
+        assert!(parse(&["--decimal-sep", ";"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_group_separator() {
+        let args = parse(&["--group-digits"]).unwrap();
+        assert!(args.group_digits);
+        assert_eq!(args.group_separator, ',');
+        let args = parse(&["--group-separator", "."]).unwrap();
+        assert_eq!(args.group_separator, '.');
+        assert!(parse(&["--group-separator", ".."]).is_err());
+    }
+
+    #[test]
+    fn should_parse_hour_histogram() {
+        assert!(!parse(&[]).unwrap().hour_histogram);
+        assert!(parse(&["--hour-histogram"]).unwrap().hour_histogram);
+    }
+
+    #[test]
+    fn should_parse_bill_round_up() {
+        assert_eq!(parse(&[]).unwrap().bill_round_up, None);
+        let args = parse(&["--bill-round-up", "0.25h"]).unwrap();
+        assert_eq!(args.bill_round_up, Some(Duration::minutes(15)));
+        let args = parse(&["--bill-round-up", "1"]).unwrap();
+        assert_eq!(args.bill_round_up, Some(Duration::HOUR));
+        assert!(parse(&["--bill-round-up", "0h"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_timesheet() {
+        assert!(!parse(&[]).unwrap().timesheet);
+        assert!(parse(&["--timesheet"]).unwrap().timesheet);
+    }
+
+    #[test]
+    fn should_parse_balance_chart() {
+        assert!(!parse(&[]).unwrap().balance_chart);
+        assert!(parse(&["--balance-chart"]).unwrap().balance_chart);
+    }
+
+    #[test]
+    fn should_parse_text() {
+        let args = parse(&["--json", "--oneline", "--text"]).unwrap();
+        assert!(!args.json && !args.oneline);
+    }
+
+    #[test]
+    fn should_parse_oneline() {
+        assert!(!parse(&[]).unwrap().oneline);
+        assert!(parse(&["--oneline"]).unwrap().oneline);
+    }
+
+    #[test]
+    fn should_parse_from_last_clockin() {
+        assert!(!parse(&[]).unwrap().from_last_clockin);
+        assert!(parse(&["--from-last-clockin"]).unwrap().from_last_clockin);
+    }
+
+    #[test]
+    fn should_parse_dump_intervals() {
+        assert!(!parse(&[]).unwrap().dump_intervals);
+        assert!(parse(&["--dump-intervals"]).unwrap().dump_intervals);
+    }
+
+    #[test]
+    fn should_parse_hledger() {
+        assert!(!parse(&[]).unwrap().hledger);
+        assert!(parse(&["--hledger"]).unwrap().hledger);
+    }
+
+    #[test]
+    fn should_parse_json_lines() {
+        assert!(!parse(&[]).unwrap().json_lines);
+        assert!(parse(&["--json-lines"]).unwrap().json_lines);
+    }
+
+    #[test]
+    fn should_parse_today() {
+        let args = parse(&["--today", "2022-01-04"]).unwrap();
+        assert_eq!(
+            args.options.today,
+            Some(time::macros::date!(2022 - 01 - 04))
+        );
+        assert!(parse(&["--today", "yesterday"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_tz() {
+        let args = parse(&["--tz", "+02:00"]).unwrap();
+        assert_eq!(args.tz, Some(time::macros::offset!(+02:00)));
+        assert!(parse(&["--tz", "CEST"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_output_tz() {
+        assert_eq!(parse(&[]).unwrap().output_tz, None);
+        let args = parse(&["--output-tz", "-05:00"]).unwrap();
+        assert_eq!(args.output_tz, Some(time::macros::offset!(-05:00)));
+        assert!(parse(&["--output-tz", "EST"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_now() {
+        let args = parse(&["--now", "2022/04/22 17:00:00"]).unwrap();
+        assert_eq!(args.now, Some(time::macros::datetime!(2022-04-22 17:00:00)));
+        assert!(parse(&["--epoch-now", "1650646800"]).unwrap().now.is_some());
+        assert!(parse(&["--epoch-now", "soon"]).is_err());
+        let error = parse(&["--now", "2022/04/22 17:00:00", "--epoch-now", "1650646800"]);
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "only one of [--now] and [--epoch-now] may be given"
+        );
+    }
+
+    #[test]
+    fn should_parse_dst_aware_time_zone() {
+        let args = parse(&["--dst-aware", "CET-1CEST,M3.5.0,M10.5.0/3"]).unwrap();
+        assert!(args.options.time_zone.is_some());
+        assert!(parse(&["--dst-aware", "Nowhere/Special"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_min_day() {
+        assert_eq!(parse(&[]).unwrap().options.min_day, None);
+        let args = parse(&["--min-day", "30"]).unwrap();
+        assert_eq!(args.options.min_day, Some(Duration::minutes(30)));
+        assert!(parse(&["--min-day", "half an hour"]).is_err());
+        assert!(parse(&["--min-day", "-30"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_assume_clockout() {
+        let args = parse(&["--assume-clockout", "17:30"]).unwrap();
+        assert_eq!(
+            args.options.assume_clockout,
+            Some(time::macros::time!(17:30))
+        );
+        assert!(parse(&["--assume-clockout", "5pm"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_neg_style() {
+        assert_eq!(parse(&[]).unwrap().negative_style, NegativeStyle::Minus);
+        let args = parse(&["--neg-style", "paren"]).unwrap();
+        assert_eq!(args.negative_style, NegativeStyle::Parentheses);
+        assert!(parse(&["--neg-style", "red"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_explain() {
+        assert!(!parse(&[]).unwrap().explain);
+        assert!(parse(&["--explain"]).unwrap().explain);
+    }
+
+    #[test]
+    fn should_parse_legal_max() {
+        let args = parse(&["--legal-max", "12", "--legal-max-warn"]).unwrap();
+        assert_eq!(args.legal_max, Some(Duration::hours(12)));
+        assert!(args.legal_max_warn);
+        assert!(!parse(&[]).unwrap().legal_max_warn);
+    }
+
+    #[test]
+    fn should_parse_break_after() {
+        assert_eq!(parse(&[]).unwrap().break_after, None);
+        let args = parse(&["--break-after", "1.5"]).unwrap();
+        assert_eq!(args.break_after, Some(Duration::minutes(90)));
+        assert!(parse(&["--break-after", "0"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_recompute_target() {
+        let args = parse(&["--recompute-target", "6"]).unwrap();
+        assert_eq!(args.recompute_target, Some(Duration::hours(6)));
+        assert!(parse(&["--recompute-target", "0"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_plan_leave() {
+        let args = parse(&["--plan-leave", "17:30"]).unwrap();
+        assert_eq!(args.plan_leave, Some(time::macros::time!(17:30)));
+    }
+
+    #[test]
+    fn should_parse_in_days() {
+        assert!(!parse(&[]).unwrap().in_days);
+        assert!(parse(&["--in-days"]).unwrap().in_days);
+    }
+
+    #[test]
+    fn should_parse_no_open_session() {
+        assert!(!parse(&[]).unwrap().options.no_open_session);
+        assert!(
+            parse(&["--no-open-session"])
+                .unwrap()
+                .options
+                .no_open_session
+        );
+    }
+
+    #[test]
+    fn should_parse_clock_markers() {
+        let args = parse(&[]).unwrap();
+        assert_eq!(
+            (
+                args.options.in_marker.as_str(),
+                args.options.out_marker.as_str()
+            ),
+            ("i", "o")
+        );
+        let args = parse(&["--in-marker", "start", "--out-marker", "stop"]).unwrap();
+        assert_eq!(args.options.in_marker, "start");
+        assert_eq!(args.options.out_marker, "stop");
+        assert!(parse(&["--in-marker", ""]).is_err());
+        assert!(parse(&["--out-marker", "clock out"]).is_err());
+        assert!(parse(&["--in-marker", "o"]).is_err());
+        assert!(parse(&["--in-marker", "x", "--out-marker", "x"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_comment_chars() {
+        assert_eq!(parse(&[]).unwrap().options.comment_chars, vec!['#', ';']);
+        let args = parse(&["--comment-chars", "%"]).unwrap();
+        assert_eq!(args.options.comment_chars, vec!['%']);
+        assert!(parse(&["--comment-chars", ""])
+            .unwrap()
+            .options
+            .comment_chars
+            .is_empty());
+    }
+
+    #[test]
+    fn should_parse_merge_adjacent() {
+        assert!(!parse(&[]).unwrap().options.merge_adjacent);
+        let args = parse(&["--merge-adjacent"]).unwrap();
+        assert!(args.options.merge_adjacent);
+    }
+
+    #[test]
+    fn should_parse_trust_clockout_account() {
+        assert!(!parse(&[]).unwrap().options.trust_clockout_account);
+        let args = parse(&["--trust-clockout-account"]).unwrap();
+        assert!(args.options.trust_clockout_account);
+    }
+
+    #[test]
+    fn should_parse_skip_leading_out() {
+        assert!(!parse(&[]).unwrap().options.skip_leading_out);
+        let args = parse(&["--skip-leading-out"]).unwrap();
+        assert!(args.options.skip_leading_out);
+    }
+
+    #[test]
+    fn should_parse_resume_latest() {
+        assert!(!parse(&[]).unwrap().options.resume_latest);
+        let args = parse(&["--resume-latest"]).unwrap();
+        assert!(args.options.resume_latest);
+    }
+
+    #[test]
+    fn should_parse_days() {
+        assert!(!parse(&[]).unwrap().days);
+        assert!(parse(&["--days"]).unwrap().days);
+    }
+
+    #[test]
+    fn should_parse_years() {
+        let args = parse(&[]).unwrap();
+        assert!(!args.years);
+        assert_eq!(args.fiscal_year_start, Month::January);
+        let args = parse(&["--years", "--fiscal-year-start", "april"]).unwrap();
+        assert!(args.years);
+        assert_eq!(args.fiscal_year_start, Month::April);
+        assert!(parse(&["--fiscal-year-start", "0"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_part_of_day() {
+        assert!(!parse(&[]).unwrap().part_of_day);
+        assert!(parse(&["--part-of-day"]).unwrap().part_of_day);
+    }
+
+    #[test]
+    fn should_parse_billable() {
+        let args = parse(&["--billable", "acme:*", "--billable", "initech:*"]).unwrap();
+        assert_eq!(args.options.billable, vec!["acme:*", "initech:*"]);
+    }
+
+    #[test]
+    fn should_parse_baseline() {
+        let args = parse(&["--baseline", "/tmp/monday.json"]).unwrap();
+        assert_eq!(args.baseline, Some(PathBuf::from("/tmp/monday.json")));
+    }
+
+    #[test]
+    fn should_parse_out_and_tee() {
+        let args = parse(&[]).unwrap();
+        assert!(args.out.is_none() && !args.tee);
+        let args = parse(&["--out", "/tmp/status.txt", "--tee"]).unwrap();
+        assert_eq!(args.out, Some(PathBuf::from("/tmp/status.txt")));
+        assert!(args.tee);
+    }
+
+    #[test]
+    fn should_parse_json() {
+        assert!(!parse(&[]).unwrap().json);
+        assert!(parse(&["--json"]).unwrap().json);
+    }
+
+    #[test]
+    fn should_parse_group_by() {
+        let args = parse(&["--group-by", "^([^:]+):"]).unwrap();
+        assert_eq!(args.group_by.unwrap().as_str(), "^([^:]+):");
+        assert!(parse(&["--group-by", "(unclosed"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_sections() {
+        assert_eq!(parse(&[]).unwrap().sections, None);
+        let args = parse(&["--sections", "leave,first-in"]).unwrap();
+        assert_eq!(
+            args.sections,
+            Some(vec!["leave".to_owned(), "first-in".to_owned()])
+        );
+        let error = parse(&["--sections", "leave,lunch"]).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("unknown section: [lunch], expected one of first-in, last-in,"));
+    }
+
+    #[test]
+    fn should_parse_watch() {
+        let args = parse(&["--watch", "--interval", "5"]).unwrap();
+        assert!(args.watch);
+        assert_eq!(args.interval, Duration::seconds(5));
+        assert_eq!(parse(&[]).unwrap().interval, Duration::minutes(1));
+        assert!(parse(&["--interval", "0"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_status() {
+        assert!(!parse(&[]).unwrap().status);
+        let args = parse(&["status", "--timelog", "timelog.txt"]).unwrap();
+        assert!(args.status);
+        assert_eq!(args.timelog, Some(PathBuf::from("timelog.txt")));
+    }
+
+    #[test]
+    fn should_parse_progress() {
+        assert!(!parse(&[]).unwrap().progress);
+        assert!(parse(&["--progress"]).unwrap().progress);
+    }
+
+    #[test]
+    fn should_parse_width() {
+        assert_eq!(parse(&[]).unwrap().width, DEFAULT_WIDTH);
+        assert_eq!(parse(&["--width", "100"]).unwrap().width, 100);
+        assert!(parse(&["--width", "5"]).is_err());
+    }
+
+    #[test]
+    fn should_reject_unknown_argument() {
+        assert!(parse(&["--frobnicate"]).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+//! Reads defaults for the command line arguments from a `.tlsumrc` file.
+//!
+//! The file is a flat subset of TOML, e.g.
+//!
+//! ```toml
+//! target_hours = 7.5
+//! non_working_accounts = ["admin:*", "lunch"]
+//! output_format = "oneline"
+//! ```
+//!
+//! There are no keys for colors or the timestamp format: tlsum prints no
+//! colors and timelogs have a single timestamp format.
+
+use crate::cli::{parse_hours, Args};
+use anyhow::{anyhow, bail, Context, Result};
+use lib::parse_hour_minute;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+use time::Duration;
+
+const CONFIG_FILE_NAME: &str = ".tlsumrc";
+
+/// The settings read from a `.tlsumrc` file, all of them optional.
+#[derive(Debug, Default, PartialEq)]
+pub struct Config {
+    pub timelog: Option<PathBuf>,
+    pub target_hours: Option<String>,
+    pub lunch: Option<u32>,
+    pub expected_start: Option<String>,
+    pub group_digits: Option<bool>,
+    /// Glob patterns of the accounts excluded from all figures.
+    pub non_working_accounts: Vec<String>,
+    /// One of `text`, `json`, `oneline`, `timesheet` or `balance-chart`.
+    pub output_format: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    Number(String),
+    Bool(bool),
+    Array(Vec<String>),
+}
+
+impl Value {
+    fn string(self, key: &str) -> Result<String> {
+        match self {
+            Self::String(string) => Ok(string),
+            _ => bail!("expected a string for [{}]", key),
+        }
+    }
+
+    fn number(self, key: &str) -> Result<String> {
+        match self {
+            Self::Number(number) => Ok(number),
+            _ => bail!("expected a number for [{}]", key),
+        }
+    }
+
+    fn bool(self, key: &str) -> Result<bool> {
+        match self {
+            Self::Bool(b) => Ok(b),
+            _ => bail!("expected true or false for [{}]", key),
+        }
+    }
+
+    fn array(self, key: &str) -> Result<Vec<String>> {
+        match self {
+            Self::Array(strings) => Ok(strings),
+            _ => bail!("expected an array of strings for [{}]", key),
+        }
+    }
+}
+
+/// Parses a quoted string at the start of `s`, returning it and the rest.
+fn parse_string(s: &str) -> Result<(String, &str)> {
+    let mut string = String::new();
+    let mut chars = s
+        .strip_prefix('"')
+        .context("expected a string")?
+        .char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((string, &s[i + 2..])),
+            '\\' => match chars.next() {
+                Some((_, '"')) => string.push('"'),
+                Some((_, '\\')) => string.push('\\'),
+                Some((_, 'n')) => string.push('\n'),
+                Some((_, 't')) => string.push('\t'),
+                _ => bail!("unsupported escape sequence"),
+            },
+            c => string.push(c),
+        }
+    }
+    bail!("unterminated string")
+}
+
+/// Parses a value at the start of `s`, returning it and the rest.
+fn parse_value(s: &str) -> Result<(Value, &str)> {
+    if s.starts_with('"') {
+        let (string, rest) = parse_string(s)?;
+        return Ok((Value::String(string), rest));
+    }
+    if let Some(mut rest) = s.strip_prefix('[') {
+        let mut strings = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(strings), after));
+            }
+            let (string, after) = parse_string(rest)?;
+            strings.push(string);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                bail!("expected , or ] in array");
+            }
+        }
+    }
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    let (token, rest) = s.split_at(end);
+    let value = match token {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        number if number.parse::<f64>().is_ok() => Value::Number(number.to_owned()),
+        other => bail!("unsupported value: [{}]", other),
+    };
+    Ok((value, rest))
+}
+
+impl Config {
+    /// Parses the contents of a `.tlsumrc` file.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut config = Self::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line_number = i + 1;
+            config
+                .parse_line(line.trim())
+                .with_context(|| format!("failed to parse line {}", line_number))?;
+        }
+        Ok(config)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<()> {
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected key = value"))?;
+        let key = key.trim();
+        let (value, rest) = parse_value(value.trim())?;
+        let rest = rest.trim();
+        if !(rest.is_empty() || rest.starts_with('#')) {
+            bail!("unexpected trailing [{}]", rest);
+        }
+        match key {
+            "timelog" => self.timelog = Some(value.string(key)?.into()),
+            "target_hours" => self.target_hours = Some(value.number(key)?),
+            "lunch" => {
+                self.lunch = Some(
+                    value
+                        .number(key)?
+                        .parse()
+                        .context("expected a whole, non-negative number of minutes")?,
+                );
+            }
+            "expected_start" => self.expected_start = Some(value.string(key)?),
+            "group_digits" => self.group_digits = Some(value.bool(key)?),
+            "non_working_accounts" => self.non_working_accounts = value.array(key)?,
+            "output_format" => self.output_format = Some(value.string(key)?),
+            other => bail!("unknown key: [{}]", other),
+        }
+        Ok(())
+    }
+
+    /// Reads `.tlsumrc` from the current directory or else from the home
+    /// directory. A missing file results in an empty configuration.
+    pub fn load() -> Result<Self> {
+        let home = env::var_os("HOME").map(|home| Path::new(&home).join(CONFIG_FILE_NAME));
+        for path in [Some(PathBuf::from(CONFIG_FILE_NAME)), home]
+            .into_iter()
+            .flatten()
+        {
+            match fs::read_to_string(&path) {
+                Ok(contents) => {
+                    return Self::parse(&contents)
+                        .with_context(|| format!("invalid {}", path.to_string_lossy()));
+                }
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => {
+                    return Err(error)
+                        .with_context(|| format!("unable to read {}", path.to_string_lossy()));
+                }
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Uses the configured settings as defaults for `args`.
+    pub fn apply(self, mut args: Args) -> Result<Args> {
+        if let Some(timelog) = self.timelog {
+            args.timelog = Some(timelog);
+        }
+        if let Some(target_hours) = self.target_hours {
+            args.options.target = parse_hours(&target_hours)?;
+        }
+        if let Some(lunch) = self.lunch {
+            args.options.lunch = Some(Duration::minutes(lunch.into()));
+        }
+        if let Some(expected_start) = self.expected_start {
+            args.expected_start = Some(parse_hour_minute(&expected_start)?);
+        }
+        if let Some(group_digits) = self.group_digits {
+            args.group_digits = group_digits;
+        }
+        if !self.non_working_accounts.is_empty() {
+            args.options.exclude_accounts = self.non_working_accounts;
+            args.configured_excludes = true;
+        }
+        if let Some(output_format) = self.output_format {
+            match output_format.as_str() {
+                "text" => {}
+                "json" => args.json = true,
+                "oneline" => args.oneline = true,
+                "timesheet" => args.timesheet = true,
+                "balance-chart" => args.balance_chart = true,
+                other => bail!("unknown output format: [{}]", other),
+            }
+            args.configured_output = true;
+        }
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_all_keys() {
+        let config = Config::parse(
+            r#"
+# my defaults
+timelog = "/home/fred/timelog"
+target_hours = 7.5 # four day week soon
+lunch = 30
+expected_start = "09:00"
+group_digits = true
+non_working_accounts = ["admin:*", "lunch"]
+output_format = "oneline"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                timelog: Some(PathBuf::from("/home/fred/timelog")),
+                target_hours: Some("7.5".to_owned()),
+                lunch: Some(30),
+                expected_start: Some("09:00".to_owned()),
+                group_digits: Some(true),
+                non_working_accounts: vec!["admin:*".to_owned(), "lunch".to_owned()],
+                output_format: Some("oneline".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn should_parse_escaped_strings() {
+        let config = Config::parse(r#"timelog = "C:\\logs\\\"time\"""#).unwrap();
+        assert_eq!(config.timelog, Some(PathBuf::from(r#"C:\logs\"time""#)));
+    }
+
+    #[test]
+    fn should_report_line_of_invalid_entry() {
+        let error = Config::parse("lunch = 30\ncolour = \"always\"").unwrap_err();
+        assert_eq!(error.to_string(), "failed to parse line 2");
+        assert!(Config::parse("lunch = \"30\"").is_err());
+        assert!(Config::parse("lunch = -30").is_err());
+        assert!(Config::parse("lunch").is_err());
+        assert!(Config::parse("non_working_accounts = [\"a\" \"b\"]").is_err());
+    }
+
+    #[test]
+    fn should_use_config_unless_overridden_by_flag() {
+        let config = || Config {
+            target_hours: Some("7".to_owned()),
+            ..Config::default()
+        };
+        let args = config().apply(Args::default()).unwrap();
+        let args = args.update(Vec::new()).unwrap();
+        assert_eq!(args.options.target, Duration::hours(7));
+        let args = config().apply(Args::default()).unwrap();
+        let args = args
+            .update(vec!["--target-hours".to_owned(), "9".to_owned()])
+            .unwrap();
+        assert_eq!(args.options.target, Duration::hours(9));
+    }
+
+    #[test]
+    fn should_replace_configured_accounts_and_output_by_flags() {
+        let config = || Config {
+            non_working_accounts: vec!["admin:*".to_owned()],
+            output_format: Some("oneline".to_owned()),
+            ..Config::default()
+        };
+        let args = config().apply(Args::default()).unwrap();
+        let args = args.update(Vec::new()).unwrap();
+        assert_eq!(args.options.exclude_accounts, vec!["admin:*".to_owned()]);
+        assert!(args.oneline);
+        let args = config().apply(Args::default()).unwrap();
+        let args = args
+            .update(
+                [
+                    "--exclude-account",
+                    "lunch",
+                    "--exclude-account",
+                    "sick",
+                    "--json",
+                ]
+                .map(str::to_owned),
+            )
+            .unwrap();
+        assert_eq!(
+            args.options.exclude_accounts,
+            vec!["lunch".to_owned(), "sick".to_owned()]
+        );
+        assert!(args.json);
+        assert!(!args.oneline);
+        let json = || Config {
+            output_format: Some("json".to_owned()),
+            ..Config::default()
+        };
+        let args = json().apply(Args::default()).unwrap();
+        let args = args.update(vec!["--matrix".to_owned()]).unwrap();
+        assert!(args.matrix && !args.json);
+        let args = json().apply(Args::default()).unwrap();
+        let args = args.update(vec!["--text".to_owned()]).unwrap();
+        assert!(!args.json);
+        assert!(Config {
+            output_format: Some("xml".to_owned()),
+            ..Config::default()
+        }
+        .apply(Args::default())
+        .is_err());
+    }
+}
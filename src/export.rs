@@ -0,0 +1,314 @@
+//! Renders summarized timelog data in machine readable formats.
+
+use crate::{
+    decimal_hours, decimal_hours_with, top_level_account, Day, Interval, RoundMode, UNASSIGNED,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+use time::{format_description::FormatItem, macros::format_description, Date, Duration};
+
+/// Timestamps are written as ISO 8601 without an offset.
+pub(crate) const ISO_FORMAT: &[FormatItem<'static>] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]");
+const HLEDGER_DATE_FORMAT: &[FormatItem<'static>] = format_description!("[year]-[month]-[day]");
+const HLEDGER_TIME_FORMAT: &[FormatItem<'static>] = format_description!("[hour]:[minute]");
+
+/// The field delimiter of the CSV exports, `;` when the decimal separator
+/// is a comma, keeping the numbers in a single field.
+fn csv_delimiter(decimal_separator: char) -> char {
+    if decimal_separator == ',' {
+        ';'
+    } else {
+        ','
+    }
+}
+
+/// Quotes a CSV field when it contains the delimiter, a quote or a newline.
+fn csv_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Renders the hours worked per date and top-level account as CSV. The
+/// header lists the accounts, each row is a date, days an account was not
+/// worked on have `0` hours. The hours use `decimal_separator`, see
+/// [`csv_delimiter`] for the delimiter.
+#[must_use]
+#[inline]
+pub fn matrix(days: &BTreeMap<Date, Day>, decimal_separator: char) -> String {
+    let delimiter = csv_delimiter(decimal_separator);
+    let accounts: BTreeSet<&str> = days
+        .values()
+        .flat_map(|day| day.projects.keys())
+        .map(|account| top_level_account(account))
+        .collect();
+    let mut csv = String::from("date");
+    for account in &accounts {
+        csv.push(delimiter);
+        csv.push_str(&csv_field(account, delimiter));
+    }
+    csv.push('\n');
+    for (date, day) in days {
+        let mut row: BTreeMap<&str, Duration> = BTreeMap::new();
+        for (account, worked) in &day.projects {
+            *row.entry(top_level_account(account)).or_default() += *worked;
+        }
+        let _ = write!(csv, "{}", date);
+        for account in &accounts {
+            match row.get(account) {
+                Some(worked) => {
+                    let _ = write!(
+                        csv,
+                        "{}{}",
+                        delimiter,
+                        decimal_hours_with(*worked, decimal_separator)
+                    );
+                }
+                None => {
+                    let _ = write!(csv, "{}0", delimiter);
+                }
+            }
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Renders the time worked per hour of the day as CSV, a row per hour
+/// holding the whole minutes worked in it, e.g. `09,30`, delimited like
+/// [`matrix`].
+#[must_use]
+#[inline]
+pub fn hour_histogram(histogram: &[Duration; 24], decimal_separator: char) -> String {
+    let d = csv_delimiter(decimal_separator);
+    let mut csv = format!("hour{}minutes\n", d);
+    for (hour, worked) in histogram.iter().enumerate() {
+        let _ = writeln!(csv, "{:02}{}{}", hour, d, worked.whole_minutes());
+    }
+    csv
+}
+
+/// Renders the hours worked per date as CSV for invoicing, next to them
+/// rounded up to a multiple of `increment`, followed by a row of the
+/// totals, the billed total being the sum of the rounded days. Delimited
+/// like [`matrix`].
+#[must_use]
+#[inline]
+pub fn billing(days: &BTreeMap<Date, Day>, increment: Duration, decimal_separator: char) -> String {
+    let d = csv_delimiter(decimal_separator);
+    let hours = |duration| decimal_hours_with(duration, decimal_separator);
+    let mut csv = format!("date{d}hours{d}billed\n", d = d);
+    let mut worked = Duration::ZERO;
+    let mut billed = Duration::ZERO;
+    for (date, day) in days {
+        let rounded = RoundMode::Up.round(day.worked, increment);
+        worked += day.worked;
+        billed += rounded;
+        let _ = writeln!(
+            csv,
+            "{}{d}{}{d}{}",
+            date,
+            hours(day.worked),
+            hours(rounded),
+            d = d
+        );
+    }
+    let _ = writeln!(csv, "total{d}{}{d}{}", hours(worked), hours(billed), d = d);
+    csv
+}
+
+/// Renders every interval as a tab separated line of its start, its end,
+/// its duration in whole seconds and its account. The end of an open
+/// interval reads `open`, its duration being up to now.
+#[inline]
+pub fn intervals(
+    intervals: impl Iterator<Item = anyhow::Result<Interval>>,
+) -> anyhow::Result<String> {
+    let mut tsv = String::new();
+    for interval in intervals {
+        let interval = interval?;
+        let end = if interval.open {
+            "open".to_owned()
+        } else {
+            interval.end.format(ISO_FORMAT)?
+        };
+        let _ = writeln!(
+            tsv,
+            "{}\t{}\t{}\t{}",
+            interval.start.format(ISO_FORMAT)?,
+            end,
+            interval.duration().whole_seconds(),
+            interval.account
+        );
+    }
+    Ok(tsv)
+}
+
+/// Renders every interval as an hledger transaction dated on its start,
+/// described by its clock times, with a single unbalanced posting of its
+/// decimal hours to its account, e.g.
+///
+/// ```text
+/// 2022-01-03 * 09:00-12:30
+///     (acme:dev)  3.50h
+/// ```
+///
+/// An open interval, up to now, is marked pending with `!` instead of
+/// cleared.
+#[inline]
+pub fn hledger(
+    intervals: impl Iterator<Item = anyhow::Result<Interval>>,
+) -> anyhow::Result<String> {
+    let mut journal = String::new();
+    for interval in intervals {
+        let interval = interval?;
+        let account = if interval.account.is_empty() {
+            UNASSIGNED
+        } else {
+            &interval.account
+        };
+        let _ = writeln!(
+            journal,
+            "{} {} {}-{}\n    ({})  {}h\n",
+            interval.start.format(HLEDGER_DATE_FORMAT)?,
+            if interval.open { '!' } else { '*' },
+            interval.start.format(HLEDGER_TIME_FORMAT)?,
+            interval.end.format(HLEDGER_TIME_FORMAT)?,
+            account,
+            decimal_hours(interval.duration())
+        );
+    }
+    Ok(journal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Intervals;
+    use std::io::Cursor;
+    use time::macros::{date, datetime};
+
+    fn day(projects: &[(&str, i64)]) -> Day {
+        let projects: BTreeMap<String, Duration> = projects
+            .iter()
+            .map(|(account, minutes)| ((*account).to_owned(), Duration::minutes(*minutes)))
+            .collect();
+        Day {
+            first_punchin: datetime!(2022 - 01 - 01 09:00:00),
+            last_end: datetime!(2022 - 01 - 01 17:00:00),
+            worked: projects.values().copied().sum(),
+            projects,
+        }
+    }
+
+    #[test]
+    fn matrix_should_have_a_column_per_top_level_account() {
+        let days = [
+            (
+                date!(2022 - 01 - 03),
+                day(&[("acme:dev", 120), ("acme:meeting", 30), ("initech", 300)]),
+            ),
+            (date!(2022 - 01 - 04), day(&[("acme:dev", 480)])),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            matrix(&days, '.'),
+            "date,acme,initech\n2022-01-03,2.50,5.00\n2022-01-04,8.00,0\n"
+        );
+    }
+
+    #[test]
+    fn billing_should_round_each_day_up() {
+        let days = [
+            (date!(2022 - 01 - 03), day(&[("acme", 8 * 60 + 7)])),
+            (date!(2022 - 01 - 04), day(&[("acme", 6 * 60)])),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            billing(&days, Duration::minutes(15), '.'),
+            "date,hours,billed\n2022-01-03,8.12,8.25\n2022-01-04,6.00,6.00\ntotal,14.12,14.25\n"
+        );
+    }
+
+    #[test]
+    fn comma_decimals_should_switch_to_semicolon_delimiter() {
+        let days = [
+            (
+                date!(2022 - 01 - 03),
+                day(&[("acme:dev", 520), ("initech; inc", 30)]),
+            ),
+            (date!(2022 - 01 - 04), day(&[("acme", 6 * 60)])),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            matrix(&days, ','),
+            "date;acme;\"initech; inc\"\n2022-01-03;8,67;0,50\n2022-01-04;6,00;0\n"
+        );
+        assert_eq!(
+            billing(&days, Duration::minutes(15), ','),
+            "date;hours;billed\n2022-01-03;9,17;9,25\n2022-01-04;6,00;6,00\ntotal;15,17;15,25\n"
+        );
+    }
+
+    #[test]
+    fn intervals_should_be_a_line_per_interval() {
+        let log = "i 2022/01/03 09:00:00 acme:dev\no 2022/01/03 12:30:00\n\
+                   i 2022/01/03 13:00:00 initech\no 2022/01/03 17:00:00\n\
+                   i 2022/01/04 09:00:00\n";
+        let now = datetime!(2022 - 01 - 04 09:15:00);
+        let tsv = intervals(Intervals::new(Cursor::new(log), now)).unwrap();
+        assert_eq!(
+            tsv.lines().collect::<Vec<_>>(),
+            vec![
+                "2022-01-03T09:00:00\t2022-01-03T12:30:00\t12600\tacme:dev",
+                "2022-01-03T13:00:00\t2022-01-03T17:00:00\t14400\tinitech",
+                "2022-01-04T09:00:00\topen\t900\t",
+            ]
+        );
+    }
+
+    #[test]
+    fn hledger_should_post_the_hours_of_every_interval() {
+        let log = "i 2022/01/03 09:00:00 acme:dev\no 2022/01/03 12:30:00\n\
+                   i 2022/01/03 13:00:00 initech\no 2022/01/03 17:15:00\n\
+                   i 2022/01/04 09:00:00\n";
+        let now = datetime!(2022 - 01 - 04 09:15:00);
+        let journal = hledger(Intervals::new(Cursor::new(log), now)).unwrap();
+        assert_eq!(
+            journal,
+            "2022-01-03 * 09:00-12:30\n    (acme:dev)  3.50h\n\n\
+             2022-01-03 * 13:00-17:15\n    (initech)  4.25h\n\n\
+             2022-01-04 ! 09:00-09:15\n    (<unassigned>)  0.25h\n\n"
+        );
+    }
+
+    #[test]
+    fn hour_histogram_should_have_a_row_per_hour() {
+        let mut histogram = [Duration::ZERO; 24];
+        histogram[9] = Duration::minutes(30);
+        histogram[10] = Duration::HOUR;
+        let csv = hour_histogram(&histogram, '.');
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 25);
+        assert_eq!(lines[..2], ["hour,minutes", "00,0"]);
+        assert_eq!(lines[10..12], ["09,30", "10,60"]);
+        assert_eq!(lines[24], "23,0");
+    }
+
+    #[test]
+    fn csv_field_should_quote_delimiters() {
+        assert_eq!(csv_field("acme", ','), "acme");
+        assert_eq!(csv_field("acme, inc", ','), "\"acme, inc\"");
+        assert_eq!(csv_field("the \"best\"", ','), "\"the \"\"best\"\"\"");
+        assert_eq!(csv_field("acme, inc", ';'), "acme, inc");
+        assert_eq!(csv_field("acme; inc", ';'), "\"acme; inc\"");
+    }
+}
@@ -0,0 +1,57 @@
+/// Matches `text` against a shell style glob `pattern`, where `*` matches any
+/// number of characters and `?` matches exactly one character.
+pub(crate) fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` seen in the pattern, and the position in
+    // the text it was matched against, to backtrack to on a mismatch.
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_literal() {
+        assert!(matches("acme", "acme"));
+        assert!(!matches("acme", "acme-corp"));
+    }
+
+    #[test]
+    fn should_match_star() {
+        assert!(matches("admin:*", "admin:mail"));
+        assert!(matches("admin:*", "admin:"));
+        assert!(matches("*:fred", "e:fc:fred"));
+        assert!(matches("*", ""));
+        assert!(!matches("admin:*", "acme:admin"));
+    }
+
+    #[test]
+    fn should_match_question_mark() {
+        assert!(matches("e:f?:fred", "e:fc:fred"));
+        assert!(!matches("e:f?:fred", "e:f:fred"));
+    }
+}
@@ -0,0 +1,395 @@
+//! The JSON projection of a [`Summary`], durations being whole seconds and
+//! timestamps ISO 8601, without an offset unless one is given.
+
+use crate::{export::ISO_FORMAT, Interval, ParseError, Summary, Warning};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io::Write, path::Path};
+use time::{
+    format_description::FormatItem, macros::format_description, PrimitiveDateTime, UtcOffset,
+};
+
+/// Timestamps are written as ISO 8601 with this offset when one is given.
+const ISO_OFFSET_FORMAT: &[FormatItem<'static>] = format_description!(
+    "[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]"
+);
+
+/// The summary as written by `--json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SummaryJson {
+    pub avg_session_seconds: Option<i64>,
+    pub avg_worked_seconds: Option<i64>,
+    pub billable_seconds: i64,
+    pub clocked_in: bool,
+    pub first_punchin_today: Option<String>,
+    pub focus_ratio_today: Option<f64>,
+    pub last_punchin: Option<String>,
+    pub last_punchout: Option<String>,
+    pub non_billable_seconds: i64,
+    pub num_days_worked: u32,
+    pub num_sessions: u32,
+    pub overtime_seconds: i64,
+    pub projected_month_seconds: Option<i64>,
+    pub projects_seconds: BTreeMap<String, i64>,
+    pub still_to_work_seconds: i64,
+    pub still_to_work_target_seconds: i64,
+    pub time_to_leave: Option<String>,
+    pub time_to_leave_target: Option<String>,
+    pub total_worked_seconds: i64,
+    /// The skipped lines and the other warnings, empty in a baseline saved
+    /// before warnings were part of the summary.
+    #[serde(default)]
+    pub warnings: Vec<WarningJson>,
+    pub worked_today_seconds: i64,
+}
+
+/// A skipped line or another warning as written by `--json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarningJson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl From<&Warning> for WarningJson {
+    fn from(warning: &Warning) -> Self {
+        Self {
+            line: warning.line,
+            message: warning.message.clone(),
+        }
+    }
+}
+
+/// An error as written by `--json`, `line` being set for errors in the
+/// timelog itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorJson {
+    pub error: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+/// Formats `date_time`, taken to be at `offset` when given, which is then
+/// appended, e.g. `2022-01-03T10:30:00+02:00`.
+fn format_timestamp(
+    offset: Option<UtcOffset>,
+    date_time: PrimitiveDateTime,
+) -> Result<String, time::error::Format> {
+    match offset {
+        Some(offset) => date_time.assume_offset(offset).format(ISO_OFFSET_FORMAT),
+        None => date_time.format(ISO_FORMAT),
+    }
+}
+
+fn timestamp(offset: Option<UtcOffset>, date_time: Option<PrimitiveDateTime>) -> Option<String> {
+    date_time.and_then(|date_time| format_timestamp(offset, date_time).ok())
+}
+
+impl From<&Summary> for SummaryJson {
+    fn from(summary: &Summary) -> Self {
+        Self::new(summary, None)
+    }
+}
+
+impl SummaryJson {
+    /// The summary with its timestamps at `offset`, naive when `None`.
+    #[must_use]
+    pub fn new(summary: &Summary, offset: Option<UtcOffset>) -> Self {
+        Self {
+            avg_session_seconds: summary.avg_session.map(|d| d.whole_seconds()),
+            avg_worked_seconds: summary.avg_worked.map(|d| d.whole_seconds()),
+            billable_seconds: summary.billable.whole_seconds(),
+            clocked_in: summary.clocked_in,
+            first_punchin_today: timestamp(offset, summary.first_punchin_today),
+            focus_ratio_today: summary.focus_ratio_today,
+            last_punchin: timestamp(offset, summary.last_punchin),
+            last_punchout: timestamp(offset, summary.last_punchout),
+            non_billable_seconds: summary.non_billable.whole_seconds(),
+            num_days_worked: summary.num_days_worked,
+            num_sessions: summary.num_sessions,
+            overtime_seconds: summary.overtime.whole_seconds(),
+            projected_month_seconds: summary.projected_month.map(|d| d.whole_seconds()),
+            projects_seconds: summary
+                .projects
+                .iter()
+                .map(|(account, worked)| (account.clone(), worked.whole_seconds()))
+                .collect(),
+            still_to_work_seconds: summary.still_to_work.whole_seconds(),
+            still_to_work_target_seconds: summary.still_to_work_8.whole_seconds(),
+            time_to_leave: timestamp(offset, summary.time_to_leave),
+            time_to_leave_target: timestamp(offset, summary.time_to_leave_8),
+            total_worked_seconds: summary.total_worked.whole_seconds(),
+            warnings: summary
+                .skipped
+                .iter()
+                .map(Warning::from)
+                .chain(summary.warnings.iter().cloned())
+                .map(|warning| WarningJson::from(&warning))
+                .collect(),
+            worked_today_seconds: summary.worked_today.whole_seconds(),
+        }
+    }
+}
+
+impl From<&anyhow::Error> for ErrorJson {
+    fn from(error: &anyhow::Error) -> Self {
+        match error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<ParseError>())
+        {
+            Some(parse_error) => Self {
+                error: parse_error.message.clone(),
+                line: Some(parse_error.line),
+            },
+            None => Self {
+                error: format!("{:#}", error),
+                line: None,
+            },
+        }
+    }
+}
+
+/// An interval as written by `--json-lines`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntervalJson {
+    pub start: String,
+    pub end: String,
+    pub seconds: i64,
+    pub account: String,
+    /// Set for a trailing clock in, its end being now.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub open: bool,
+}
+
+impl TryFrom<&Interval> for IntervalJson {
+    type Error = anyhow::Error;
+
+    #[inline]
+    fn try_from(interval: &Interval) -> Result<Self, Self::Error> {
+        Self::new(interval, None)
+    }
+}
+
+impl IntervalJson {
+    /// The interval with its timestamps at `offset`, naive when `None`.
+    #[inline]
+    pub fn new(interval: &Interval, offset: Option<UtcOffset>) -> anyhow::Result<Self> {
+        Ok(Self {
+            start: format_timestamp(offset, interval.start)?,
+            end: format_timestamp(offset, interval.end)?,
+            seconds: interval.duration().whole_seconds(),
+            account: interval.account.clone(),
+            open: interval.open,
+        })
+    }
+}
+
+/// Writes every interval as a line of JSON to `out` as soon as it is
+/// parsed, flushing after each, and returns the number of lines written.
+/// The timestamps are at `offset`, naive when `None`.
+#[inline]
+pub fn interval_lines(
+    intervals: impl Iterator<Item = anyhow::Result<Interval>>,
+    offset: Option<UtcOffset>,
+    out: &mut dyn Write,
+) -> anyhow::Result<usize> {
+    let mut lines = 0;
+    for interval in intervals {
+        let json = serde_json::to_string(&IntervalJson::new(&interval?, offset)?)?;
+        writeln!(out, "{}", json)
+            .and_then(|()| out.flush())
+            .context("unable to write interval")?;
+        lines += 1;
+    }
+    Ok(lines)
+}
+
+/// Renders the summary as a single line of JSON, its timestamps at
+/// `offset`, naive when `None`.
+#[must_use]
+#[inline]
+pub fn summary(summary: &Summary, offset: Option<UtcOffset>) -> String {
+    serde_json::to_string(&SummaryJson::new(summary, offset)).unwrap_or_default()
+}
+
+/// Renders the error as a single line of JSON.
+#[must_use]
+#[inline]
+pub fn error(error: &anyhow::Error) -> String {
+    serde_json::to_string(&ErrorJson::from(error)).unwrap_or_default()
+}
+
+/// Reads a summary previously written by `--json`.
+#[inline]
+pub fn load_summary<P: AsRef<Path>>(path: P) -> anyhow::Result<SummaryJson> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("unable to read {}", path.to_string_lossy()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("{} is not a JSON summary", path.to_string_lossy()))
+}
+
+/// Formats signed seconds compactly, e.g. `+1h12m`, `+8h` or `-45m`.
+fn signed_hours_mins(seconds: i64) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.unsigned_abs() / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}{}m", sign, minutes),
+        (hours, 0) => format!("{}{}h", sign, hours),
+        (hours, minutes) => format!("{}{}h{}m", sign, hours, minutes),
+    }
+}
+
+/// Describes how `current` differs from `baseline`, a line per figure.
+#[must_use]
+pub fn baseline_deltas(baseline: &SummaryJson, current: &SummaryJson) -> Vec<String> {
+    [
+        (
+            "Overtime",
+            current.overtime_seconds - baseline.overtime_seconds,
+        ),
+        (
+            "Total",
+            current.total_worked_seconds - baseline.total_worked_seconds,
+        ),
+    ]
+    .into_iter()
+    .map(|(figure, delta)| format!("{}: {} since baseline", figure, signed_hours_mins(delta)))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{summarize_reader, Intervals, Options};
+    use std::io::Cursor;
+    use time::macros::datetime;
+
+    #[test]
+    fn summary_should_use_seconds_and_iso_timestamps() {
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 10:30:00\n";
+        let now = datetime!(2022-01-03 11:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        let json: SummaryJson = serde_json::from_str(&super::summary(&summary, None)).unwrap();
+        assert_eq!(json.total_worked_seconds, 5400);
+        assert_eq!(json.projects_seconds["acme"], 5400);
+        assert_eq!(json.last_punchout.as_deref(), Some("2022-01-03T10:30:00"));
+        assert!(!json.clocked_in);
+    }
+
+    fn summary_json(overtime_seconds: i64, total_worked_seconds: i64) -> SummaryJson {
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 10:30:00\n";
+        let now = datetime!(2022-01-03 11:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        SummaryJson {
+            overtime_seconds,
+            total_worked_seconds,
+            ..SummaryJson::from(&summary)
+        }
+    }
+
+    #[test]
+    fn baseline_deltas_should_be_signed_hours_and_minutes() {
+        let baseline = summary_json(3600, 100 * 3600);
+        let current = summary_json(3600 + 72 * 60, 108 * 3600);
+        assert_eq!(
+            baseline_deltas(&baseline, &current),
+            vec![
+                "Overtime: +1h12m since baseline",
+                "Total: +8h since baseline"
+            ]
+        );
+        let behind = summary_json(3600 - 45 * 60, 100 * 3600);
+        assert_eq!(
+            baseline_deltas(&baseline, &behind),
+            vec!["Overtime: -45m since baseline", "Total: +0m since baseline"]
+        );
+    }
+
+    #[test]
+    fn summary_should_append_offset_to_leave_times() {
+        let log = "i 2022/01/03 09:00:00 acme\n";
+        let now = datetime!(2022-01-03 11:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        let naive: SummaryJson = serde_json::from_str(&super::summary(&summary, None)).unwrap();
+        assert_eq!(naive.time_to_leave.as_deref(), Some("2022-01-03T17:00:00"));
+        let offset = Some(time::macros::offset!(+02:00));
+        let json: SummaryJson = serde_json::from_str(&super::summary(&summary, offset)).unwrap();
+        assert_eq!(
+            json.time_to_leave.as_deref(),
+            Some("2022-01-03T17:00:00+02:00")
+        );
+        assert_eq!(
+            json.time_to_leave_target.as_deref(),
+            Some("2022-01-03T17:00:00+02:00")
+        );
+    }
+
+    #[test]
+    fn load_summary_should_read_what_summary_wrote() {
+        let path = std::env::temp_dir().join(format!("tlsum-baseline-{}", std::process::id()));
+        let json = summary_json(60, 120);
+        fs::write(&path, serde_json::to_string(&json).unwrap()).unwrap();
+        assert_eq!(load_summary(&path).unwrap(), json);
+        fs::remove_file(&path).unwrap();
+        assert!(load_summary(&path).is_err());
+    }
+
+    #[test]
+    fn summary_should_list_skipped_lines_as_warnings() {
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 10:30:00\nx 2022/01/03 11:00:00\n";
+        let now = datetime!(2022-01-03 12:00:00);
+        let options = Options {
+            lenient: true,
+            ..Options::default()
+        };
+        let summary = summarize_reader(Cursor::new(log), &now, &options).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&super::summary(&summary, None)).unwrap();
+        let warnings = json["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0]["line"], 3);
+        assert!(warnings[0]["message"].is_string());
+    }
+
+    #[test]
+    fn error_should_carry_the_line_of_a_state_error() {
+        let log = "i 2022/01/03 09:00:00 acme\ni 2022/01/03 10:30:00 acme\n";
+        let now = datetime!(2022-01-03 11:00:00);
+        let error = summarize_reader(Cursor::new(log), &now, &Options::default())
+            .err()
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&super::error(&error)).unwrap();
+        assert_eq!(json["line"], 2);
+        assert!(json["error"].as_str().unwrap().contains("clock"));
+    }
+
+    #[test]
+    fn interval_lines_should_write_a_line_per_interval() {
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 10:30:00\n\
+                   i 2022/01/03 11:00:00 beta\no 2022/01/03 12:00:00\n\
+                   i 2022/01/03 13:00:00 acme\n";
+        let now = datetime!(2022-01-03 13:30:00);
+        let mut out = Vec::new();
+        let lines = interval_lines(Intervals::new(Cursor::new(log), now), None, &mut out).unwrap();
+        assert_eq!(lines, 3);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 3);
+        assert_eq!(
+            out.lines().next().unwrap(),
+            r#"{"start":"2022-01-03T09:00:00","end":"2022-01-03T10:30:00","seconds":5400,"account":"acme"}"#
+        );
+        let open: IntervalJson = serde_json::from_str(out.lines().last().unwrap()).unwrap();
+        assert!(open.open);
+        assert_eq!(open.seconds, 1800);
+    }
+
+    #[test]
+    fn error_should_omit_the_line_of_other_errors() {
+        let error = anyhow::anyhow!("unable to read timelog");
+        assert_eq!(
+            super::error(&error),
+            r#"{"error":"unable to read timelog"}"#
+        );
+    }
+}
@@ -1,39 +1,96 @@
+pub mod export;
+mod glob;
+/// Requires the `serde` feature, enabled by default.
+#[cfg(feature = "serde")]
+pub mod json;
+
 use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
 use std::{
-    cmp::max,
-    env,
+    borrow::Cow,
+    cmp::{max, min},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    env, fmt,
     fs::File,
     io,
-    io::BufRead,
-    ops::{Range, RangeTo},
+    io::{BufRead, Read, Write},
+    ops::{RangeFrom, RangeTo},
     path::Path,
     path::PathBuf,
     str::FromStr,
+    sync::OnceLock,
 };
 use time::{
-    error::Parse, format_description::FormatItem, macros::format_description, Date, Duration,
-    OffsetDateTime, PrimitiveDateTime,
+    format_description::FormatItem, macros::format_description, Date, Duration, Month,
+    OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday,
 };
+use tz::TimeZone;
+
+/// The version of the library API, raised on every incompatible change to
+/// [`Summary`], [`Options`] or the `summarize_*` functions. These do not
+/// depend on any cargo feature.
+pub const API_VERSION: u32 = 1;
 
 /// This is the default timestamp format used by Emacs.
 const TIMESTAMP_FORMAT: &[FormatItem<'static>] =
     format_description!("[year]/[month]/[day] [hour repr:24]:[minute]:[second]");
+/// The timestamp formats accepted in a timelog, tried in order, each without
+/// and with fractional seconds, e.g. `21:33:23.500`.
+const TIMESTAMP_INPUT_FORMATS: [(&str, &[FormatItem<'static>], &[FormatItem<'static>]); 2] = [
+    (
+        "YYYY/MM/DD HH:MM:SS",
+        TIMESTAMP_FORMAT,
+        format_description!("[year]/[month]/[day] [hour repr:24]:[minute]:[second].[subsecond]"),
+    ),
+    (
+        "YYYY-MM-DD HH:MM:SS",
+        format_description!("[year]-[month]-[day] [hour repr:24]:[minute]:[second]"),
+        format_description!("[year]-[month]-[day] [hour repr:24]:[minute]:[second].[subsecond]"),
+    ),
+];
 const HOUR_MINUTE_FORMAT: &[FormatItem<'static>] =
     format_description!("[hour repr:24]:[minute]:[second]");
+const HOUR_MINUTE_INPUT_FORMAT: &[FormatItem<'static>] =
+    format_description!("[hour repr:24]:[minute]");
+const DATE_INPUT_FORMAT: &[FormatItem<'static>] = format_description!("[year]-[month]-[day]");
+const UTC_OFFSET_INPUT_FORMAT: &[FormatItem<'static>] =
+    format_description!("[offset_hour sign:mandatory]:[offset_minute]");
+const DATE_FORMAT: &[FormatItem<'static>] = format_description!("[year]/[month]/[day]");
 const DATE_TIME_FORMAT: &[FormatItem<'static>] =
     format_description!("[hour repr:24]:[minute]:[second]  [year]/[month]/[day]");
 
 const TIMELOG_ENV_VAR_NAME: &str = "TIMELOG";
-const COMMENT: char = '#';
+/// The markers of a comment, either a whole line or, preceded by
+/// whitespace, the rest of a line. Ledger uses `;`.
+const COMMENTS: [char; 2] = ['#', ';'];
+/// The start, after the comment character, of a comment recording a
+/// partial absence, e.g. `# vacation 2022/04/22 4h`, see
+/// [`Summary::vacations`].
+const VACATION_DIRECTIVE: &str = "vacation ";
+
+/// The project time without an account is accumulated under.
+pub const UNASSIGNED: &str = "<unassigned>";
+/// The group of the accounts not matching the pattern of [`rollup_by_regex`].
+pub const UNMATCHED: &str = "<unmatched>";
 
 /// The default file path Emacs uses to record timeclock-in|out records.
 const DEFAULT_TIMELOG_PATH: &str = ".emacs.d/.local/etc/timelog";
 
+/// The number of hours expected to be worked on a single day by default.
+pub const DEFAULT_TARGET: Duration = Duration::hours(8);
+/// A gap between two intervals on the same day longer than this is a break.
+const BREAK_THRESHOLD: Duration = Duration::minutes(20);
+
 //           1         2
 // 012345678901234567890123456
 // i 2022/04/22 21:33:23 e:fc:fred
+// i 2022/04/22 21:33:23.500 e:fc:fred
+// i 1650656003 e:fc:fred
+// i 2022/04/22 09:00:00 - 2022/04/22 17:00:00 e:fc:fred
 const CLOCK_TYPE_RANGE: RangeTo<usize> = ..1;
-const DATE_TIME_RANGE: Range<usize> = 2..21;
+const TIMESTAMP_RANGE: RangeFrom<usize> = 2..;
+const CLOCK_TYPE_COLUMN: usize = 1;
+const TIMESTAMP_COLUMN: usize = TIMESTAMP_RANGE.start + 1;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 enum ClockType {
@@ -54,251 +111,2696 @@ impl FromStr for ClockType {
     }
 }
 
+/// How [`Options::round`] rounds a duration to a multiple of the
+/// granularity.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RoundMode {
+    /// Up to the next multiple.
+    Up,
+    /// Down to the previous multiple.
+    Down,
+    /// To the closest multiple, halfway values up.
+    #[default]
+    Nearest,
+    /// To the closest multiple, halfway values to the even multiple, so
+    /// halves don't add up to a bias over many intervals.
+    Bankers,
+}
+
+impl FromStr for RoundMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            "nearest" => Ok(Self::Nearest),
+            "bankers" => Ok(Self::Bankers),
+            other => Err(anyhow!(
+                "unknown round mode: [{}], expected up, down, nearest or bankers",
+                other
+            )),
+        }
+    }
+}
+
+impl RoundMode {
+    /// Rounds `duration` to a multiple of `granularity`.
+    #[must_use]
+    pub fn round(self, duration: Duration, granularity: Duration) -> Duration {
+        let step = granularity.whole_nanoseconds();
+        if step <= 0 {
+            return duration;
+        }
+        let nanos = duration.whole_nanoseconds();
+        let (quotient, remainder) = (nanos.div_euclid(step), nanos.rem_euclid(step));
+        let round_up = match self {
+            Self::Up => remainder > 0,
+            Self::Down => false,
+            Self::Nearest => 2 * remainder >= step,
+            Self::Bankers => 2 * remainder > step || (2 * remainder == step && quotient % 2 != 0),
+        };
+        let multiples = quotient + i128::from(round_up);
+        granularity * i32::try_from(multiples).unwrap_or(i32::MAX)
+    }
+}
+
+/// Up to when [`Summary::overtime`] counts the time worked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OvertimeThrough {
+    /// The end of yesterday, leaving out today's work and its expectation.
+    #[default]
+    Yesterday,
+    /// Now, including today's work so far against today's target.
+    Today,
+}
+
+impl FromStr for OvertimeThrough {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yesterday" => Ok(Self::Yesterday),
+            "today" => Ok(Self::Today),
+            other => Err(anyhow!(
+                "unknown overtime reference: [{}], expected today or yesterday",
+                other
+            )),
+        }
+    }
+}
+
+/// Settings that influence how a timelog is summarized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The time expected to be worked on a single day.
+    pub target: Duration,
+    /// The contractual working day, driving `still_to_work_8` and
+    /// `time_to_leave_8` while the overtime and the balance based figures
+    /// use `target`. Same as `target` when unset.
+    pub contract: Option<Duration>,
+    /// When set, the first worked day carries no expectation, i.e. the
+    /// overtime is computed against `(num_days_worked - 1) * target`. This
+    /// is the historical behavior: since today's work is not part of the
+    /// overtime, the expectation for today is left out as well. When unset
+    /// the expectation is `num_days_worked * target`.
+    pub exempt_first_day: bool,
+    /// When set and the only worked day is not today, that day is held
+    /// against the target, so a single day of exactly `target` has no
+    /// overtime instead of all of it.
+    pub first_day_overtime: bool,
+    /// When set, no day counts for more than `target`, like payroll systems
+    /// that record no overtime. The excess ends up in
+    /// [`Summary::uncounted_overtime`] instead of in any other figure.
+    pub cap_daily: bool,
+    /// When set, Saturdays and Sundays without work do not break a streak,
+    /// see [`Summary::current_streak`].
+    pub streak_skips_weekends: bool,
+    /// Whether the overtime includes the time worked today.
+    pub overtime_through: OvertimeThrough,
+    /// When set, every parsed interval is written to stderr.
+    pub verbose: bool,
+    /// When set, lines that cannot be used are skipped instead of failing
+    /// the summary.
+    pub lenient: bool,
+    /// When set, the problems that are otherwise reported in
+    /// [`Summary::warnings`] fail the summary.
+    pub strict: bool,
+    /// When set, a session whose clock in names no account is attributed to
+    /// the account on its clock out line, if any. The account of the clock
+    /// in always takes precedence.
+    pub trust_clockout_account: bool,
+    /// When set, clock outs before the first clock in are ignored instead of
+    /// failing the summary, e.g. for a log whose top was truncated.
+    pub skip_leading_out: bool,
+    /// When set, a clock in while still clocked in discards the earlier,
+    /// dangling clock in with a warning instead of failing the summary.
+    pub resume_latest: bool,
+    /// The token starting a clock in line, `i` by default, e.g. `start`.
+    pub in_marker: String,
+    /// The token starting a clock out line, `o` by default, e.g. `stop`.
+    pub out_marker: String,
+    /// The duration of the lunch break, added to the time to leave as long
+    /// as no break has been taken today.
+    pub lunch: Option<Duration>,
+    /// Glob patterns of the accounts to include, all accounts are included
+    /// when empty.
+    pub accounts: Vec<String>,
+    /// Glob patterns of the accounts to exclude, applied after `accounts`.
+    pub exclude_accounts: Vec<String>,
+    /// When set, only the accounts whose [`account_owner`] is this name are
+    /// included, for logs shared by several people.
+    pub owner: Option<String>,
+    /// Glob patterns of the billable accounts, the time of any other
+    /// account is non billable. Unlike excluded time, non billable time does
+    /// count as worked.
+    pub billable: Vec<String>,
+    /// Pairs of old and new account names. An account equal to an old name,
+    /// or starting with the old name followed by `:`, has that part replaced
+    /// by the new name before it is filtered and accumulated.
+    pub aliases: Vec<(String, String)>,
+    /// Leading segments of accounts, like `e:fc:`, of which the first one an
+    /// account starts with is removed before aliases are resolved.
+    pub strip_prefixes: Vec<String>,
+    /// When set, the duration of every interval is rounded to a multiple of
+    /// this granularity, as directed by `round_mode`.
+    pub round: Option<Duration>,
+    pub round_mode: RoundMode,
+    /// When set, a trailing clock in on a day before today is clocked out at
+    /// this time of its own day instead of being counted up to `now`.
+    pub assume_clockout: Option<Time>,
+    /// When set, a trailing clock in without a clock out is left out
+    /// instead of being counted up to `now`, making the summary independent
+    /// of the moment it is computed.
+    pub no_open_session: bool,
+    /// When set, an interval starting at the end of the previous one, e.g.
+    /// when switching accounts, continues its session in
+    /// [`Summary::num_sessions`] and [`Summary::avg_session`].
+    pub merge_adjacent: bool,
+    /// When set, a past day with less time worked than this is left out of
+    /// [`Summary::num_days_worked`] and [`Summary::avg_worked`], and so is
+    /// not expected in the overtime either, while its time still counts in
+    /// [`Summary::total_worked`] and the overtime. Today is always counted.
+    pub min_day: Option<Duration>,
+    /// When set, the summary is computed as if it were this moment instead
+    /// of `now`. Intervals starting later are dropped, intervals ending
+    /// later are cut short.
+    pub as_of: Option<PrimitiveDateTime>,
+    /// The date whose figures are reported as today's, the worked time,
+    /// the first punch in and the time to leave, instead of the date of
+    /// `now`. The elapsed time of an open session still runs up to `now`.
+    pub today: Option<Date>,
+    /// The time zone the timelog was recorded in. When set, the duration of
+    /// an interval is the time that actually elapsed, i.e. an interval that
+    /// spans a daylight saving time transition is an hour shorter or longer
+    /// than the difference of its naive timestamps.
+    pub time_zone: Option<TimeZone>,
+    /// The markers of a comment, `#` and `;` by default, none when empty.
+    pub comment_chars: Vec<char>,
+    /// The target hours per date they take effect on, each applying up to
+    /// the next, see [`Options::target_on`]. Empty for `target` throughout.
+    pub schedule: BTreeMap<Date, Duration>,
+}
+
+impl Options {
+    /// The target of `date`: the one of the latest [`Options::schedule`]
+    /// entry taking effect on or before it, else `target`.
+    #[must_use]
+    #[inline]
+    pub fn target_on(&self, date: Date) -> Duration {
+        self.schedule
+            .range(..=date)
+            .next_back()
+            .map_or(self.target, |(_, target)| *target)
+    }
+
+    fn elapsed(&self, interval: &Interval) -> anyhow::Result<Duration> {
+        let elapsed = match &self.time_zone {
+            None => interval.duration(),
+            Some(zone) => {
+                let offset_change =
+                    utc_offset(zone, interval.end)? - utc_offset(zone, interval.start)?;
+                interval.duration() - Duration::seconds(offset_change.into())
+            }
+        };
+        Ok(self.round.map_or(elapsed, |granularity| {
+            self.round_mode.round(elapsed, granularity)
+        }))
+    }
+
+    /// The interval with its account as summarized, stripped of
+    /// `strip_prefixes` and renamed by `aliases` and its end cut short at
+    /// `as_of`, or `None` when that account is left out by `accounts`,
+    /// `exclude_accounts` or `owner`, when it starts after `as_of` or when it
+    /// is open and `no_open_session` is set.
+    #[must_use]
+    #[inline]
+    pub fn select(&self, interval: Interval) -> Option<Interval> {
+        let account = self.resolve_account(interval.account);
+        if !self.accepts_account(&account) || (self.no_open_session && interval.open) {
+            return None;
+        }
+        let end = match self.as_of {
+            Some(as_of) if interval.start > as_of => return None,
+            Some(as_of) => min(interval.end, as_of),
+            None => interval.end,
+        };
+        Some(Interval {
+            account,
+            end,
+            ..interval
+        })
+    }
+
+    /// The intervals of the timelog read by `reader`, parsed as these options
+    /// say, up to `now`. [`Options::select`] picks those summarized.
+    #[inline]
+    pub fn intervals<R: BufRead>(&self, reader: R, now: PrimitiveDateTime) -> Intervals<R> {
+        Intervals::new(reader, now)
+            .comment_chars(&self.comment_chars)
+            .lenient(self.lenient)
+            .assume_clockout(self.assume_clockout)
+            .strict(self.strict)
+            .trust_clockout_account(self.trust_clockout_account)
+            .skip_leading_out(self.skip_leading_out)
+            .resume_latest(self.resume_latest)
+            .clock_markers(&self.in_marker, &self.out_marker)
+    }
+
+    fn resolve_account(&self, account: String) -> String {
+        self.resolve_alias(self.strip_prefix(account))
+    }
+
+    fn strip_prefix(&self, account: String) -> String {
+        self.strip_prefixes
+            .iter()
+            .find_map(|prefix| account.strip_prefix(prefix.as_str()))
+            .filter(|rest| !rest.is_empty())
+            .map_or_else(|| account.clone(), ToOwned::to_owned)
+    }
+
+    fn resolve_alias(&self, account: String) -> String {
+        for (old, new) in &self.aliases {
+            if let Some(rest) = account.strip_prefix(old.as_str()) {
+                if rest.is_empty() || rest.starts_with(':') {
+                    return format!("{}{}", new, rest);
+                }
+            }
+        }
+        account
+    }
+
+    fn accepts_account(&self, account: &str) -> bool {
+        let matches = |pattern: &String| glob::matches(pattern, account);
+        (self.accounts.is_empty() || self.accounts.iter().any(matches))
+            && !self.exclude_accounts.iter().any(matches)
+            && self
+                .owner
+                .as_ref()
+                .is_none_or(|owner| account_owner(account) == owner)
+    }
+
+    fn is_billable(&self, account: &str) -> bool {
+        self.billable
+            .iter()
+            .any(|pattern| glob::matches(pattern, account))
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            target: DEFAULT_TARGET,
+            contract: None,
+            exempt_first_day: true,
+            first_day_overtime: false,
+            cap_daily: false,
+            streak_skips_weekends: false,
+            overtime_through: OvertimeThrough::default(),
+            verbose: false,
+            lenient: false,
+            strict: false,
+            trust_clockout_account: false,
+            skip_leading_out: false,
+            resume_latest: false,
+            in_marker: "i".to_owned(),
+            out_marker: "o".to_owned(),
+            lunch: None,
+            accounts: Vec::new(),
+            exclude_accounts: Vec::new(),
+            owner: None,
+            billable: Vec::new(),
+            aliases: Vec::new(),
+            strip_prefixes: Vec::new(),
+            round: None,
+            round_mode: RoundMode::default(),
+            assume_clockout: None,
+            no_open_session: false,
+            merge_adjacent: false,
+            min_day: None,
+            as_of: None,
+            today: None,
+            time_zone: None,
+            comment_chars: COMMENTS.to_vec(),
+            schedule: BTreeMap::new(),
+        }
+    }
+}
+
+/// The UTC offset in seconds of the naive `local` time in `zone`.
+fn utc_offset(zone: &TimeZone, local: PrimitiveDateTime) -> anyhow::Result<i32> {
+    let naive = local.assume_utc().unix_timestamp();
+    let guess = zone.find_local_time_type(naive)?.ut_offset();
+    let offset = zone
+        .find_local_time_type(naive - i64::from(guess))?
+        .ut_offset();
+    Ok(offset)
+}
+
+/// The figures of a single worked day.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Day {
+    /// The earliest clock in of the day.
+    pub first_punchin: PrimitiveDateTime,
+    /// The latest end of an interval started on the day, `now` for an open
+    /// session.
+    pub last_end: PrimitiveDateTime,
+    pub worked: Duration,
+    /// The time worked per account on the day.
+    pub projects: BTreeMap<String, Duration>,
+}
+
+/// How the first clock in of each worked day relates to an expected start.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Punctuality {
+    pub on_time_days: u32,
+    pub late_days: u32,
+    /// The average lateness of the late days.
+    pub avg_lateness: Option<Duration>,
+}
+
+/// The time worked per part of the day, see [`Summary::parts_of_day`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct PartsOfDay {
+    /// Before 12:00.
+    pub morning: Duration,
+    /// From 12:00 up to 18:00.
+    pub afternoon: Duration,
+    /// From 18:00.
+    pub evening: Duration,
+}
+
+/// The worked days that are not regular working days, see
+/// [`Summary::off_days_worked`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct OffDaysWorked {
+    pub saturdays: u32,
+    pub sundays: u32,
+    /// The worked days among the holidays, whatever day of the week.
+    pub holidays: u32,
+}
+
+/// The figures of clocking out at a planned moment, see
+/// [`Summary::leave_plan`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct LeavePlan {
+    pub worked_today: Duration,
+    /// The time worked today beyond the target.
+    pub day_overtime: Duration,
+    /// The cumulative overtime including today.
+    pub balance: Duration,
+}
+
+/// A worked day of [`Summary::daily_balance`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct DayBalance {
+    pub date: Date,
+    /// The time worked on the day minus the target of the day.
+    pub deviation: Duration,
+    /// The deviations summed up to and including the day.
+    pub balance: Duration,
+}
+
 pub struct Summary {
+    /// The total time worked divided by the number of sessions.
+    pub avg_session: Option<Duration>,
     pub avg_worked: Option<Duration>,
+    /// The time worked on accounts matching [`Options::billable`].
+    pub billable: Duration,
+    pub break_taken_today: bool,
+    /// Whether the timelog ends with a clock in.
+    pub clocked_in: bool,
+    /// The number of consecutive worked days up to today, or up to
+    /// yesterday as long as nothing was worked today.
+    pub current_streak: u32,
+    /// The worked days, a day being the date an interval started on.
+    pub days: BTreeMap<Date, Day>,
     pub first_punchin_today: Option<PrimitiveDateTime>,
+    /// The time worked today divided by the time from the first punch in
+    /// today up to the end of the last interval today, i.e. the share of
+    /// the time spent at the office that was worked.
+    pub focus_ratio_today: Option<f64>,
+    /// The time worked per hour of the day, from `00:00` up to `23:00`,
+    /// every interval split over the hours it spans. An interval counts for
+    /// the time it adds to `total_worked`, after `round`, `cap_daily` and
+    /// `dst_aware`, from its start on.
+    pub hour_histogram: [Duration; 24],
     pub last_punchin: Option<PrimitiveDateTime>,
     pub last_punchout: Option<PrimitiveDateTime>,
+    /// The last date each account was worked on, keyed like `projects`.
+    pub last_worked: HashMap<String, Date>,
+    /// The largest number of consecutive worked days.
+    pub longest_streak: u32,
+    /// The time worked on all other accounts, `billable + non_billable`
+    /// being `total_worked`.
+    pub non_billable: Duration,
     pub num_days_worked: u32,
+    /// The number of clocked intervals, including an open one.
+    pub num_sessions: u32,
     pub overtime: Duration,
+    /// The time worked this month so far plus `avg_worked` for every
+    /// weekday left in the month after today.
+    pub projected_month: Option<Duration>,
+    /// The time worked per account.
+    pub projects: HashMap<String, Duration>,
+    /// The time of the closed intervals only, i.e. `total_worked` without
+    /// an open session counted up to `now`.
+    pub recorded_total: Duration,
+    /// The lines skipped in lenient mode.
+    pub skipped: Vec<ParseError>,
+    /// The population standard deviation of the time worked per day, over
+    /// the same days as `avg_worked`.
+    pub stddev_worked: Option<Duration>,
     pub still_to_work: Duration,
     pub still_to_work_8: Duration,
+    /// The target of every weekday of this week up to today minus the time
+    /// worked this week, see [`workdays_this_week`]. A weekend day adds no
+    /// target of its own, the workdays being Monday to Friday.
+    pub still_to_work_week: Duration,
     pub time_to_leave: Option<PrimitiveDateTime>,
     pub time_to_leave_8: Option<PrimitiveDateTime>,
     pub total_worked: Duration,
+    /// The time worked beyond `target` on a day, left out of all other
+    /// figures when [`Options::cap_daily`] is set.
+    pub uncounted_overtime: Duration,
+    /// The problems that did not stop the summary, e.g. an unreadable file
+    /// passed to [`summarize_files`] or a timestamp after now.
+    pub warnings: Vec<Warning>,
+    /// The date the figures of today are for, [`Options::today`] or else the
+    /// date of `now`.
+    pub today: Date,
+    /// The hours of absence per date recorded by `# vacation` directives,
+    /// lowering the time expected on that date up to the target.
+    pub vacations: BTreeMap<Date, Duration>,
     pub worked_today: Duration,
 }
 
+/// The figures accumulated while reading a timelog, from which the
+/// [`Summary`] is derived.
+#[derive(Debug, Default)]
+struct Tally {
+    worked_today: Duration,
+    first_punchin_today: Option<PrimitiveDateTime>,
+    last_end_today: Option<PrimitiveDateTime>,
+    last_punchin: Option<PrimitiveDateTime>,
+    last_punchout: Option<PrimitiveDateTime>,
+    total_worked: Duration,
+    recorded_total: Duration,
+    uncounted_overtime: Duration,
+    billable: Duration,
+    non_billable: Duration,
+    num_days_worked: u32,
+    num_sessions: u32,
+    clocked_in: bool,
+    break_taken_today: bool,
+    projects: HashMap<String, Duration>,
+    last_worked: HashMap<String, Date>,
+    hour_histogram: [Duration; 24],
+    days: BTreeMap<Date, Day>,
+    skipped: Vec<ParseError>,
+    warnings: Vec<Warning>,
+    vacations: BTreeMap<Date, Duration>,
+}
+
+/// The population standard deviation of `durations`, `None` when empty.
+/// Computed in a single pass with Welford's method over whole seconds.
+fn standard_deviation(durations: impl Iterator<Item = Duration>) -> Option<Duration> {
+    let mut count = 0_u32;
+    let mut mean = 0.0_f64;
+    let mut squares = 0.0_f64;
+    for duration in durations {
+        count += 1;
+        let seconds = duration.as_seconds_f64();
+        let delta = seconds - mean;
+        mean += delta / f64::from(count);
+        squares += delta * (seconds - mean);
+    }
+    (count > 0).then(|| Duration::seconds_f64((squares / f64::from(count)).sqrt()))
+}
+
+/// The number of days the target is expected for up to `through`, see
+/// [`Options::exempt_first_day`] and [`Options::first_day_overtime`].
+fn expected_days(
+    num_days_worked: u32,
+    worked_today: bool,
+    through: OvertimeThrough,
+    options: &Options,
+) -> u32 {
+    let single_past_day = num_days_worked == 1 && !worked_today;
+    let until_yesterday = if options.first_day_overtime && single_past_day {
+        1
+    } else if options.exempt_first_day {
+        num_days_worked.saturating_sub(1)
+    } else {
+        num_days_worked
+    };
+    // Without the exemption today is expected already.
+    let today = through == OvertimeThrough::Today && worked_today && options.exempt_first_day;
+    until_yesterday + u32::from(today)
+}
+
+/// The time worked up to `through` minus what `target` per expected day
+/// asks for.
+fn overtime(
+    total_worked: Duration,
+    worked_today: Duration,
+    num_days_worked: u32,
+    clocked_today: bool,
+    target: Duration,
+    through: OvertimeThrough,
+    options: &Options,
+) -> Duration {
+    if num_days_worked == 0 {
+        return Duration::ZERO;
+    }
+    let worked = match through {
+        OvertimeThrough::Yesterday => total_worked - worked_today,
+        OvertimeThrough::Today => total_worked,
+    };
+    worked - (expected_days(num_days_worked, clocked_today, through, options) * target)
+}
+
+/// What `overtime` is off by when the days expected up to `through` are
+/// held against their [`Options::target_on`] rather than `target`. The
+/// expected days are the last ones counted, today included when expected.
+fn schedule_correction(
+    days: &BTreeMap<Date, Day>,
+    first_punchin_today: Option<PrimitiveDateTime>,
+    num_days_worked: u32,
+    through: OvertimeThrough,
+    options: &Options,
+) -> Duration {
+    if options.schedule.is_empty() {
+        return Duration::ZERO;
+    }
+    expected_dates(days, first_punchin_today, num_days_worked, through, options)
+        .iter()
+        .map(|date| options.target - options.target_on(*date))
+        .sum()
+}
+
+/// The dates of the [`expected_days`] up to `through`: the last ones
+/// counted, today included when expected.
+fn expected_dates(
+    days: &BTreeMap<Date, Day>,
+    first_punchin_today: Option<PrimitiveDateTime>,
+    num_days_worked: u32,
+    through: OvertimeThrough,
+    options: &Options,
+) -> Vec<Date> {
+    let today = first_punchin_today.map(|first| first.date());
+    let expected = expected_days(num_days_worked, today.is_some(), through, options) as usize;
+    let mut dates: Vec<Date> = days
+        .iter()
+        .filter(|(date, day)| {
+            Some(**date) != today && options.min_day.is_none_or(|min| day.worked >= min)
+        })
+        .map(|(date, _)| *date)
+        .collect();
+    if let Some(today) = today.filter(|_| expected > dates.len()) {
+        dates.push(today);
+    }
+    dates.split_off(dates.len().saturating_sub(expected))
+}
+
+/// The time of `vacations` on the days expected up to `through`, each day
+/// at most its `target`, which is not expected of those days. A day that
+/// is not expected, like the exempt first day, has no hours to lower.
+fn vacation(
+    vacations: &BTreeMap<Date, Duration>,
+    days: &BTreeMap<Date, Day>,
+    first_punchin_today: Option<PrimitiveDateTime>,
+    num_days_worked: u32,
+    target: impl Fn(Date) -> Duration,
+    through: OvertimeThrough,
+    options: &Options,
+) -> Duration {
+    expected_dates(days, first_punchin_today, num_days_worked, through, options)
+        .iter()
+        .filter_map(|date| vacations.get(date).map(|hours| min(*hours, target(*date))))
+        .sum()
+}
+
 impl Summary {
     #[must_use]
-    fn new(
-        worked_today: Duration,
-        first_punchin_today: Option<PrimitiveDateTime>,
-        last_punchin: Option<PrimitiveDateTime>,
-        last_punchout: Option<PrimitiveDateTime>,
-        total_worked: Duration,
-        num_days_worked: u32,
-        now: &PrimitiveDateTime,
-        clocked_in: bool,
-    ) -> Self {
-        let avg_worked = total_worked.checked_div(num_days_worked as i32);
-        let total_worked_until_prev = total_worked - worked_today;
-        let overtime = if num_days_worked > 0 {
-            total_worked_until_prev - ((num_days_worked - 1_u32) * 8_u32 * Duration::HOUR)
-        } else {
-            Duration::ZERO
+    fn new(tally: Tally, now: &PrimitiveDateTime, options: &Options) -> Self {
+        let Tally {
+            worked_today,
+            first_punchin_today,
+            last_end_today,
+            last_punchin,
+            last_punchout,
+            total_worked,
+            recorded_total,
+            uncounted_overtime,
+            billable,
+            non_billable,
+            num_days_worked,
+            num_sessions,
+            clocked_in,
+            break_taken_today,
+            projects,
+            last_worked,
+            hour_histogram,
+            days,
+            skipped,
+            warnings,
+            vacations,
+        } = tally;
+        let today_worked = first_punchin_today.map(|first| first.date());
+        let (trivial_days, trivial_worked) = options.min_day.map_or((0, Duration::ZERO), |min| {
+            days.iter()
+                .filter(|(date, day)| day.worked < min && Some(**date) != today_worked)
+                .fold((0, Duration::ZERO), |(count, worked), (_, day)| {
+                    (count + 1, worked + day.worked)
+                })
+        });
+        let num_days_worked = num_days_worked - trivial_days;
+        let avg_worked = (total_worked - trivial_worked).checked_div(num_days_worked as i32);
+        let avg_session = total_worked.checked_div(num_sessions as i32);
+        let stddev_worked = standard_deviation(
+            days.iter()
+                .filter(|(date, day)| {
+                    options
+                        .min_day
+                        .is_none_or(|min| day.worked >= min || Some(**date) == today_worked)
+                })
+                .map(|(_, day)| day.worked),
+        );
+        let overtime_through = |through| {
+            overtime(
+                total_worked,
+                worked_today,
+                num_days_worked,
+                first_punchin_today.is_some(),
+                options.target,
+                through,
+                options,
+            ) + schedule_correction(
+                &days,
+                first_punchin_today,
+                num_days_worked,
+                through,
+                options,
+            )
+        };
+        let vacation_through = |through| {
+            vacation(
+                &vacations,
+                &days,
+                first_punchin_today,
+                num_days_worked,
+                |date| options.target_on(date),
+                through,
+                options,
+            )
         };
-        let still_to_work_8 = (8_u32 * Duration::HOUR) - worked_today;
-        let still_to_work = still_to_work_8 - overtime;
-        let time_to_leave = clocked_in.then(|| *now + still_to_work);
-        let time_to_leave_8 = clocked_in.then(|| *now + still_to_work_8);
+        let overtime =
+            overtime_through(options.overtime_through) + vacation_through(options.overtime_through);
+        let today = options.today.unwrap_or_else(|| now.date());
+        let target_today = options.target_on(today);
+        let vacation_today = vacations
+            .get(&today)
+            .map_or(Duration::ZERO, |hours| min(*hours, target_today));
+        let still_to_work_8 =
+            options.contract.unwrap_or(target_today) - vacation_today - worked_today;
+        // Without the exemption the overtime through yesterday holds today
+        // against its target, less its vacation, already, which is left to
+        // the time still to work here.
+        let today_expected = first_punchin_today.is_some() && !options.exempt_first_day;
+        let balance_before_today = overtime_through(OvertimeThrough::Yesterday)
+            + vacation_through(OvertimeThrough::Yesterday)
+            + if today_expected {
+                target_today - vacation_today
+            } else {
+                Duration::ZERO
+            };
+        let still_to_work = target_today - vacation_today - worked_today - balance_before_today;
+        let lunch = options
+            .lunch
+            .filter(|_| !break_taken_today)
+            .unwrap_or(Duration::ZERO);
+        let time_to_leave = clocked_in.then(|| *now + still_to_work + lunch);
+        let time_to_leave_8 = clocked_in.then(|| *now + still_to_work_8 + lunch);
+        let worked_this_month = days
+            .range(today.replace_day(1).unwrap_or(today)..)
+            .take_while(|(date, _)| date.month() == today.month())
+            .map(|(_, day)| day.worked)
+            .sum::<Duration>();
+        let projected_month =
+            avg_worked.map(|avg| worked_this_month + avg * remaining_workdays(today));
+        let monday = today - Duration::days(today.weekday().number_days_from_monday().into());
+        let worked_this_week = days
+            .range(monday..=today)
+            .map(|(_, day)| day.worked)
+            .sum::<Duration>();
+        let vacation_this_week = vacations
+            .range(monday..=today)
+            .map(|(date, hours)| min(*hours, options.target_on(*date)))
+            .sum::<Duration>();
+        let target_this_week = (0..workdays_this_week(today))
+            .map(|day| options.target_on(monday + Duration::days(day.into())))
+            .sum::<Duration>();
+        let still_to_work_week = target_this_week - worked_this_week - vacation_this_week;
+        let (current_streak, longest_streak) =
+            streaks(days.keys().copied(), today, options.streak_skips_weekends);
+        let focus_ratio_today = first_punchin_today
+            .zip(last_end_today)
+            .map(|(start, end)| end - start)
+            .filter(|span| span.is_positive())
+            .map(|span| worked_today / span);
         Self {
+            avg_session,
             avg_worked,
+            billable,
+            break_taken_today,
+            clocked_in,
+            current_streak,
+            days,
             first_punchin_today,
+            focus_ratio_today,
+            hour_histogram,
             last_punchin,
             last_punchout,
+            last_worked,
+            longest_streak,
+            non_billable,
             num_days_worked,
+            num_sessions,
             overtime,
+            projected_month,
+            projects,
+            recorded_total,
+            skipped,
+            stddev_worked,
             still_to_work,
             still_to_work_8,
+            still_to_work_week,
             time_to_leave,
             time_to_leave_8,
             total_worked,
+            uncounted_overtime,
+            warnings,
+            today,
+            vacations,
             worked_today,
         }
     }
 }
 
-#[inline]
-pub fn timelog_path() -> Result<PathBuf> {
-    let time_log = env::var_os(TIMELOG_ENV_VAR_NAME)
-        .map_or_else(|| PathBuf::from(DEFAULT_TIMELOG_PATH), PathBuf::from);
-    if time_log.exists() {
-        Ok(time_log)
-    } else {
-        bail!("time log file [{:?}] does not exist", time_log)
+impl Summary {
+    /// The number of days the target is expected for in `overtime`.
+    #[must_use]
+    #[inline]
+    pub fn expected_days(&self, options: &Options) -> u32 {
+        expected_days(
+            self.num_days_worked,
+            self.first_punchin_today.is_some(),
+            options.overtime_through,
+            options,
+        )
     }
-}
 
-fn parse_line(s: &str) -> anyhow::Result<(ClockType, PrimitiveDateTime)> {
-    let clock_type_slice = s
-        .get(CLOCK_TYPE_RANGE)
-        .ok_or_else(|| anyhow::anyhow!("got empty slice, expected 'i'| 'o'"))?;
-    let clock_type: ClockType = clock_type_slice.parse()?;
-    let date_time_slice = s
-        .get(DATE_TIME_RANGE)
-        .ok_or_else(|| anyhow::anyhow!(format!("expected slice with size 18")))?;
-    let date_time = parse_timestamp(date_time_slice)
-        .with_context(|| format!("unable to parse timestamp: [{}]", date_time_slice))?;
-    Ok((clock_type, date_time))
-}
+    /// The hours expected in `overtime`, the target in effect on each of the
+    /// [`Summary::expected_days`], see [`Options::target_on`].
+    #[must_use]
+    #[inline]
+    pub fn expected_hours(&self, options: &Options) -> Duration {
+        options.target * self.expected_days(options)
+            - schedule_correction(
+                &self.days,
+                self.first_punchin_today,
+                self.num_days_worked,
+                options.overtime_through,
+                options,
+            )
+    }
 
-#[derive(Debug, PartialEq, Eq)]
-enum States {
-    ExpectingClockIn,
-    ExpectingClockOut,
-}
+    /// The hours of `vacations` credited in `overtime`, those on the
+    /// [`Summary::expected_days`], each at most the target of its date.
+    #[must_use]
+    #[inline]
+    pub fn vacation(&self, options: &Options) -> Duration {
+        vacation(
+            &self.vacations,
+            &self.days,
+            self.first_punchin_today,
+            self.num_days_worked,
+            |date| options.target_on(date),
+            options.overtime_through,
+            options,
+        )
+    }
 
-fn summarize_lines(reader: Box<dyn BufRead>, now: &PrimitiveDateTime) -> anyhow::Result<Summary> {
-    let lines = reader.lines();
-    let mut state = States::ExpectingClockIn;
-    let mut clockin = PrimitiveDateTime::MIN;
-    let mut worked_today: Duration = Duration::ZERO;
-    let mut first_punchin_today: Option<PrimitiveDateTime> = None;
-    let mut last_punchout: Option<PrimitiveDateTime> = None;
-    let mut last_punchin: Option<PrimitiveDateTime> = None;
-    let mut total_worked: Duration = Duration::ZERO;
-    let mut num_days_worked: u32 = 0;
-    let mut line_number: usize = 0;
-    let mut previous_date: Date = PrimitiveDateTime::MIN.date();
-    let today = OffsetDateTime::now_local()?.date();
-    for line in lines {
-        line_number += 1;
-        let ip = line.with_context(|| format!("failed to read line {}", line_number))?;
-        if ip.starts_with(COMMENT) || ip.is_empty() {
-            continue;
+    /// The overtime as a fraction of [`Summary::expected_hours`], or `None`
+    /// when nothing is expected, e.g. a single exempt day.
+    #[must_use]
+    #[inline]
+    pub fn overtime_ratio(&self, options: &Options) -> Option<f64> {
+        let expected = self.expected_hours(options);
+        expected.is_positive().then(|| self.overtime / expected)
+    }
+
+    /// The cumulative overtime had the daily target always been `target`,
+    /// over the same worked days, regardless of [`Options::schedule`].
+    #[must_use]
+    #[inline]
+    pub fn overtime_at(&self, target: Duration, options: &Options) -> Duration {
+        overtime(
+            self.total_worked,
+            self.worked_today,
+            self.num_days_worked,
+            self.first_punchin_today.is_some(),
+            target,
+            options.overtime_through,
+            options,
+        ) + vacation(
+            &self.vacations,
+            &self.days,
+            self.first_punchin_today,
+            self.num_days_worked,
+            |_| target,
+            options.overtime_through,
+            options,
+        )
+    }
+
+    /// The live balance: the overtime including today's work so far against
+    /// today's target, as long as anything was worked today. With the
+    /// default [`OvertimeThrough::Yesterday`] this is `overtime` plus
+    /// `worked_today` minus the target, the target left out when
+    /// [`Options::exempt_first_day`] is unset since `overtime` holds today
+    /// against it already. Through today it is `overtime` itself.
+    #[must_use]
+    #[inline]
+    pub fn total_overtime_including_today(&self, options: &Options) -> Duration {
+        let options = Options {
+            overtime_through: OvertimeThrough::Today,
+            ..options.clone()
+        };
+        overtime(
+            self.total_worked,
+            self.worked_today,
+            self.num_days_worked,
+            self.first_punchin_today.is_some(),
+            options.target,
+            OvertimeThrough::Today,
+            &options,
+        ) + vacation(
+            &self.vacations,
+            &self.days,
+            self.first_punchin_today,
+            self.num_days_worked,
+            |date| options.target_on(date),
+            OvertimeThrough::Today,
+            &options,
+        ) + schedule_correction(
+            &self.days,
+            self.first_punchin_today,
+            self.num_days_worked,
+            OvertimeThrough::Today,
+            &options,
+        )
+    }
+
+    /// Whether nothing was clocked on `now`'s date while clocked out, e.g.
+    /// after forgetting to clock in in the morning.
+    #[must_use]
+    #[inline]
+    pub fn no_entries_today(&self, now: &PrimitiveDateTime) -> bool {
+        !self.clocked_in
+            && max(self.last_punchin, self.last_punchout)
+                .is_none_or(|last| last.date() < now.date())
+    }
+
+    /// The figures of clocking out at the moment this summary was computed
+    /// for, i.e. pass the planned clock out as `now` to
+    /// [`summarize_reader`] to have the open session end there. `today` is
+    /// the date of that moment.
+    #[must_use]
+    #[inline]
+    pub fn leave_plan(&self, today: Date, options: &Options) -> LeavePlan {
+        let target = options.target_on(today);
+        let vacation = self
+            .vacations
+            .get(&today)
+            .map_or(Duration::ZERO, |hours| min(*hours, target));
+        LeavePlan {
+            worked_today: self.worked_today,
+            day_overtime: self.worked_today + vacation - target,
+            balance: self.total_overtime_including_today(options),
         }
+    }
 
-        let (clock_type, time_stamp) =
-            parse_line(&ip).with_context(|| format!("failed to parse line {}", line_number))?;
-        state = (match (state, clock_type) {
-            (States::ExpectingClockIn, ClockType::In) => {
-                let current_date = time_stamp.date();
-                if previous_date != current_date {
-                    worked_today = Duration::ZERO;
-                    num_days_worked += 1;
-                    if time_stamp.date() == today && first_punchin_today.is_none() {
-                        first_punchin_today = Some(time_stamp);
-                    }
-                    previous_date = current_date;
-                }
-                clockin = time_stamp;
-                if let Some(lpi) = last_punchin {
-                    last_punchin = Some(max(lpi, time_stamp));
+    /// The deviation from the target of every worked day up to
+    /// [`Options::overtime_through`], with a running total that ends at
+    /// `overtime`. Vacation counts as worked, and the first day carries no
+    /// target when [`Options::exempt_first_day`] leaves it out.
+    #[must_use]
+    #[inline]
+    pub fn daily_balance(&self, options: &Options) -> Vec<DayBalance> {
+        let today = self
+            .first_punchin_today
+            .filter(|_| options.overtime_through == OvertimeThrough::Yesterday)
+            .map(|first| first.date());
+        let days: Vec<_> = self
+            .days
+            .iter()
+            .filter(|(date, _)| Some(**date) != today)
+            .collect();
+        // Days below `min_day` are not counted as worked, nor expected.
+        let worked_today = self.first_punchin_today.map(|first| first.date());
+        let trivial = |date: &Date, day: &Day| {
+            options.min_day.is_some_and(|min| day.worked < min) && Some(*date) != worked_today
+        };
+        let counted = days
+            .iter()
+            .filter(|(date, day)| !trivial(date, day))
+            .count();
+        let mut exempted = counted > self.expected_days(options) as usize;
+        let mut balance = Duration::ZERO;
+        days.into_iter()
+            .map(|(date, day)| {
+                let target = if trivial(date, day) {
+                    Duration::ZERO
+                } else if exempted {
+                    exempted = false;
+                    Duration::ZERO
                 } else {
-                    last_punchin = Some(time_stamp);
+                    options.target_on(*date)
+                };
+                let vacation = self.vacations.get(date).map_or(Duration::ZERO, |hours| {
+                    min(*hours, options.target_on(*date))
+                });
+                let deviation = day.worked + vacation - target;
+                balance += deviation;
+                DayBalance {
+                    date: *date,
+                    deviation,
+                    balance,
                 }
-                Ok(States::ExpectingClockOut)
+            })
+            .collect()
+    }
+
+    /// The time left until the open session has lasted `limit`, zero once
+    /// it has, or `None` when clocked out.
+    #[must_use]
+    #[inline]
+    pub fn break_due(&self, now: &PrimitiveDateTime, limit: Duration) -> Option<Duration> {
+        let start = self.last_punchin.filter(|_| self.clocked_in)?;
+        Some(max(Duration::ZERO, limit - (*now - start)))
+    }
+
+    /// The worked days, and the time worked on them, that exceed `limit`.
+    #[must_use]
+    #[inline]
+    pub fn days_over(&self, limit: Duration) -> Vec<(Date, Duration)> {
+        self.days
+            .iter()
+            .filter(|(_, day)| day.worked > limit)
+            .map(|(date, day)| (*date, day.worked))
+            .collect()
+    }
+
+    /// The time worked in the morning, the afternoon and the evening, every
+    /// interval split at 12:00 and 18:00 like in `hour_histogram`, so they
+    /// add up to `total_worked`.
+    #[must_use]
+    #[inline]
+    pub fn parts_of_day(&self) -> PartsOfDay {
+        let (morning, rest) = self.hour_histogram.split_at(12);
+        let (afternoon, evening) = rest.split_at(6);
+        PartsOfDay {
+            morning: morning.iter().copied().sum(),
+            afternoon: afternoon.iter().copied().sum(),
+            evening: evening.iter().copied().sum(),
+        }
+    }
+
+    /// The time worked per fiscal year, keyed by the year it starts in, see
+    /// [`fiscal_year`].
+    #[must_use]
+    #[inline]
+    pub fn yearly_totals(&self, fiscal_year_start: Month) -> BTreeMap<i32, Duration> {
+        let mut totals = BTreeMap::new();
+        for (date, day) in &self.days {
+            *totals
+                .entry(fiscal_year(*date, fiscal_year_start))
+                .or_default() += day.worked;
+        }
+        totals
+    }
+
+    /// Counts the worked Saturdays, Sundays and `holidays`.
+    #[must_use]
+    #[inline]
+    pub fn off_days_worked(&self, holidays: &BTreeSet<Date>) -> OffDaysWorked {
+        let mut off_days = OffDaysWorked {
+            saturdays: 0,
+            sundays: 0,
+            holidays: 0,
+        };
+        for date in self.days.keys() {
+            match date.weekday() {
+                Weekday::Saturday => off_days.saturdays += 1,
+                Weekday::Sunday => off_days.sundays += 1,
+                _ => {}
             }
-            (States::ExpectingClockOut, ClockType::Out) => {
-                if time_stamp < clockin {
-                    bail!(
-                        "clock out time before clock in time on line {}",
-                        line_number
-                    );
-                }
-                let clocked = time_stamp - clockin;
-                worked_today += clocked;
-                total_worked += clocked;
-                if let Some(lpa) = last_punchout {
-                    last_punchout = Some(max(lpa, time_stamp))
-                } else {
-                    last_punchout = Some(time_stamp);
-                }
-                Ok(States::ExpectingClockIn)
+            if holidays.contains(date) {
+                off_days.holidays += 1;
             }
-            (States::ExpectingClockIn, ClockType::Out) => Err(anyhow!(
-                "unexpected, clock out on line {}, expecting clock in",
-                line_number
-            )),
-            (States::ExpectingClockOut, ClockType::In) => Err(anyhow!(
-                "unexpected, clock in on line {}, expecting clock out",
-                line_number
-            )),
-        })?;
+        }
+        off_days
     }
-    let currently_clocked_in = States::ExpectingClockOut == state;
-    if currently_clocked_in {
-        if now < &clockin {
-            bail!("now is before clock in time on line {}", line_number);
+
+    /// Classifies every worked day as on time or late, a day is late when
+    /// its first clock in is more than `grace` after `expected_start`. The
+    /// lateness of a late day still counts from `expected_start`.
+    #[must_use]
+    #[inline]
+    pub fn punctuality(&self, expected_start: Time, grace: Duration) -> Punctuality {
+        let mut on_time_days = 0_u32;
+        let mut late_days = 0_u32;
+        let mut lateness = Duration::ZERO;
+        for (date, day) in &self.days {
+            let late = day.first_punchin - PrimitiveDateTime::new(*date, expected_start);
+            if late > grace {
+                late_days += 1;
+                lateness += late;
+            } else {
+                on_time_days += 1;
+            }
+        }
+        Punctuality {
+            on_time_days,
+            late_days,
+            avg_lateness: lateness.checked_div(late_days as i32),
         }
-        let clocked = *now - clockin;
-        worked_today += clocked;
-        total_worked += clocked;
     }
-    let summary = Summary::new(
-        if clockin.date() == now.date() {
-            worked_today
-        } else {
-            Duration::ZERO
-        },
-        first_punchin_today,
-        last_punchin,
-        last_punchout,
-        total_worked,
-        num_days_worked,
-        now,
-        currently_clocked_in,
-    );
-    Ok(summary)
 }
 
 #[inline]
-pub fn summarize_file<P>(filename: P, now: &PrimitiveDateTime) -> anyhow::Result<Summary>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(&filename)
-        .with_context(|| format!("unable to read {}", &filename.as_ref().to_string_lossy()))?;
-    summarize_lines(
-        Box::new(io::BufReader::with_capacity(512 * 1024, file)),
-        now,
-    )
+pub fn timelog_path() -> Result<PathBuf> {
+    let time_log = env::var_os(TIMELOG_ENV_VAR_NAME)
+        .map_or_else(|| PathBuf::from(DEFAULT_TIMELOG_PATH), PathBuf::from);
+    if time_log.exists() {
+        Ok(time_log)
+    } else {
+        bail!("time log file [{:?}] does not exist", time_log)
+    }
 }
 
-#[inline]
-pub fn format_time(date_time: PrimitiveDateTime) -> anyhow::Result<String> {
-    date_time
-        .time()
-        .format(HOUR_MINUTE_FORMAT)
-        .context("unable to format time")
+/// Parses a `# vacation YYYY/MM/DD <hours>h` directive, the `#` being any
+/// of `comment_chars` and the hours possibly fractional, e.g. `4.5h`. Any
+/// other comment yields `None`.
+fn parse_vacation(line: &str, comment_chars: &[char]) -> Option<(Date, Duration)> {
+    let mut fields = line
+        .strip_prefix(comment_chars)?
+        .trim_start()
+        .strip_prefix(VACATION_DIRECTIVE)?
+        .split_whitespace();
+    let date = Date::parse(fields.next()?, DATE_FORMAT).ok()?;
+    let hours: f64 = fields.next()?.strip_suffix('h')?.parse().ok()?;
+    if fields.next().is_some() || !(hours > 0.0 && hours <= 24.0) {
+        return None;
+    }
+    Some((date, Duration::seconds_f64(hours * 3600.0)))
 }
 
-#[inline]
-pub fn format_date_time(date_time: PrimitiveDateTime) -> anyhow::Result<String> {
-    date_time
-        .format(DATE_TIME_FORMAT)
-        .context("unable to format time")
+/// Removes an inline comment, starting with one of `comment_chars`, from a
+/// line.
+fn strip_comment<'a>(line: &'a str, comment_chars: &[char]) -> &'a str {
+    line.char_indices()
+        .find(|&(i, c)| comment_chars.contains(&c) && line[..i].ends_with(char::is_whitespace))
+        .map_or(line, |(i, _)| line[..i].trim_end())
 }
 
-#[must_use]
-#[inline]
-pub fn hours_mins(duration: Duration) -> String {
-    let hours = duration.whole_hours();
-    let minutes = (duration - Duration::hours(hours)).whole_minutes();
-    if hours == 0 && minutes.is_negative() {
-        format!("-{: <4} hours, {: <5} minutes", hours, i64::abs(minutes))
+/// Splits a line into its timestamp, either a date and a time or epoch
+/// seconds, and the account following it.
+fn split_timestamp(s: &str) -> Option<(&str, &str)> {
+    split_leading_timestamp(s.get(TIMESTAMP_RANGE)?)
+}
+
+/// Splits the clock out timestamp of a compact line off what follows the
+/// clock in timestamp, i.e. `- 2022/04/22 17:00:00 acme`, the `-` followed
+/// by one or more spaces.
+fn split_compact_end(rest: &str) -> Option<(&str, &str)> {
+    rest.strip_prefix('-')
+        .filter(|end| end.starts_with(' '))
+        .map(str::trim_start)
+        .filter(|end| end.starts_with(|c: char| c.is_ascii_digit()))
+        .and_then(split_leading_timestamp)
+}
+
+fn split_leading_timestamp(rest: &str) -> Option<(&str, &str)> {
+    let tokens = if is_epoch(rest.split(' ').next()?) {
+        1
     } else {
-        format!("{: <5} hours, {: <5} minutes", hours, i64::abs(minutes))
-    }
+        2
+    };
+    let end = rest
+        .match_indices(' ')
+        .nth(tokens - 1)
+        .map_or(rest.len(), |(i, _)| i);
+    Some((&rest[..end], rest[end..].trim()))
 }
 
-#[inline]
-pub fn now() -> anyhow::Result<PrimitiveDateTime> {
-    let local = OffsetDateTime::now_local()?;
-    let now = PrimitiveDateTime::new(local.date(), local.time());
-    Ok(now)
+fn parse_account(s: &str) -> &str {
+    split_timestamp(s).map_or("", |(_, rest)| {
+        split_compact_end(rest).map_or(rest, |(_, account)| account)
+    })
 }
 
-fn parse_timestamp(date_time: &str) -> Result<PrimitiveDateTime, Parse> {
-    PrimitiveDateTime::parse(date_time, TIMESTAMP_FORMAT)
+/// What is wrong with a line of a timelog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The clock in or out marker is missing or unknown.
+    ClockType,
+    /// The timestamp is missing or malformed.
+    Timestamp,
+    /// A clock in while expecting a clock out.
+    UnexpectedClockIn,
+    /// A clock out while expecting a clock in.
+    UnexpectedClockOut,
+    /// A clock out with a timestamp before the one of its clock in.
+    ClockOutBeforeClockIn,
+    /// A trailing clock in with a timestamp after `now`.
+    ClockInAfterNow,
+    /// A timestamp after `now`, e.g. a typo in the year, only an error when
+    /// strict.
+    Future,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// A line of a timelog that could not be used. Both `line` and `column`
+/// start at 1, the column is the one of the offending token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+    pub message: String,
+}
 
-    mod parse_timestamp {
-        use super::*;
-        use time::Month;
+impl fmt::Display for ParseError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
 
-        #[test]
-        fn should_parse_to_primitive_datetime() {
-            let result = parse_timestamp("2022/04/22 21:33:23").unwrap();
-            assert_eq!(2022, result.year());
-            assert_eq!(Month::April, result.month());
-            assert_eq!(22, result.day());
-            assert_eq!((21, 33, 23), result.as_hms());
+impl std::error::Error for ParseError {}
+
+/// A problem that did not stop the summary, `line` being set for the ones
+/// about a line of the timelog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl From<&ParseError> for Warning {
+    #[inline]
+    fn from(error: &ParseError) -> Self {
+        Self {
+            line: Some(error.line),
+            message: error.message.clone(),
+        }
+    }
+}
+
+impl From<String> for Warning {
+    #[inline]
+    fn from(message: String) -> Self {
+        Self {
+            line: None,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Parses a line, the returned error has its `line` left at 0. A clock in
+/// in the compact form, e.g.
+/// `i 2022/04/22 09:00:00 - 2022/04/22 17:00:00 acme`, carries its clock out
+/// as well.
+fn parse_line(
+    s: &str,
+) -> Result<(ClockType, PrimitiveDateTime, Option<PrimitiveDateTime>), ParseError> {
+    let clock_type: ClockType = s
+        .get(CLOCK_TYPE_RANGE)
+        .ok_or_else(|| anyhow::anyhow!("got empty slice, expected 'i'| 'o'"))
+        .and_then(str::parse)
+        .map_err(|e| ParseError {
+            line: 0,
+            column: CLOCK_TYPE_COLUMN,
+            kind: ParseErrorKind::ClockType,
+            message: e.to_string(),
+        })?;
+    let (timestamp, rest) = split_timestamp(s).ok_or_else(|| ParseError {
+        line: 0,
+        column: TIMESTAMP_COLUMN,
+        kind: ParseErrorKind::Timestamp,
+        message: "expected a timestamp".to_owned(),
+    })?;
+    let date_time = parse_timestamp_token(timestamp, TIMESTAMP_COLUMN)?;
+    let end = match split_compact_end(rest).filter(|_| clock_type == ClockType::In) {
+        Some((end, _)) => {
+            // The end is a slice of `s`, its offset in there is its column.
+            let column = end.as_ptr() as usize - s.as_ptr() as usize + 1;
+            Some(parse_timestamp_token(end, column)?)
+        }
+        None => None,
+    };
+    Ok((clock_type, date_time, end))
+}
+
+/// Parses a timestamp of a line, either epoch seconds or a date and a time,
+/// `column` being where it starts.
+fn parse_timestamp_token(timestamp: &str, column: usize) -> Result<PrimitiveDateTime, ParseError> {
+    let error = |message| ParseError {
+        line: 0,
+        column,
+        kind: ParseErrorKind::Timestamp,
+        message,
+    };
+    if is_epoch(timestamp) {
+        parse_epoch(timestamp)
+            .ok_or_else(|| error(format!("epoch seconds out of range: [{}]", timestamp)))
+    } else {
+        parse_timestamp(timestamp)
+            .map_err(|e| error(format!("unable to parse timestamp: [{}], {}", timestamp, e)))
+    }
+}
+
+/// Whether a timestamp is written as Unix epoch seconds, e.g. `1650656003`.
+fn is_epoch(timestamp: &str) -> bool {
+    !timestamp.is_empty() && timestamp.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Converts epoch seconds to the local time.
+fn parse_epoch(token: &str) -> Option<PrimitiveDateTime> {
+    from_epoch(token.parse().ok()?)
+}
+
+/// Converts Unix epoch seconds to the local time, the way epoch timestamps
+/// in a timelog are read.
+#[must_use]
+#[inline]
+pub fn from_epoch(seconds: i64) -> Option<PrimitiveDateTime> {
+    static LOCAL_TIME_ZONE: OnceLock<TimeZone> = OnceLock::new();
+    let zone =
+        LOCAL_TIME_ZONE.get_or_init(|| TimeZone::local().unwrap_or_else(|_| TimeZone::utc()));
+    epoch_to_local(seconds, zone)
+}
+
+fn epoch_to_local(seconds: i64, zone: &TimeZone) -> Option<PrimitiveDateTime> {
+    let offset = zone.find_local_time_type(seconds).ok()?.ut_offset();
+    let local = OffsetDateTime::from_unix_timestamp(seconds + i64::from(offset)).ok()?;
+    Some(PrimitiveDateTime::new(local.date(), local.time()))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum States {
+    ExpectingClockIn,
+    ExpectingClockOut,
+}
+
+/// A period of clocked time, from a clock in up to the matching clock out.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Interval {
+    pub start: PrimitiveDateTime,
+    pub end: PrimitiveDateTime,
+    /// The account recorded on the clock in line, empty when absent.
+    pub account: String,
+    /// Set for a trailing clock in without a clock out, `end` is then `now`.
+    pub open: bool,
+}
+
+impl Interval {
+    #[must_use]
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+}
+
+/// Iterates over the intervals recorded in a timelog. A trailing clock in
+/// without a matching clock out yields an open interval ending at `now`.
+/// Iteration stops after the first error, unless lenient, in which case the
+/// offending lines are skipped and collected instead.
+pub struct Intervals<R> {
+    lines: io::Lines<R>,
+    now: PrimitiveDateTime,
+    line_number: usize,
+    state: States,
+    clockin: PrimitiveDateTime,
+    clockin_line: usize,
+    account: String,
+    done: bool,
+    lenient: bool,
+    assume_clockout: Option<Time>,
+    strict: bool,
+    trust_clockout_account: bool,
+    skip_leading_out: bool,
+    resume_latest: bool,
+    /// Whether a clock in has been read yet.
+    clocked_in_once: bool,
+    /// The clock in and clock out tokens, when not the default `i` and `o`.
+    clock_markers: Option<(String, String)>,
+    skipped: Vec<ParseError>,
+    warnings: Vec<ParseError>,
+    vacations: BTreeMap<Date, Duration>,
+    comment_chars: Vec<char>,
+}
+
+impl<R: BufRead> Intervals<R> {
+    #[must_use]
+    #[inline]
+    pub fn new(reader: R, now: PrimitiveDateTime) -> Self {
+        Self {
+            lines: reader.lines(),
+            now,
+            line_number: 0,
+            state: States::ExpectingClockIn,
+            clockin: PrimitiveDateTime::MIN,
+            clockin_line: 0,
+            account: String::new(),
+            done: false,
+            lenient: false,
+            assume_clockout: None,
+            strict: false,
+            trust_clockout_account: false,
+            skip_leading_out: false,
+            resume_latest: false,
+            clocked_in_once: false,
+            clock_markers: None,
+            skipped: Vec::new(),
+            warnings: Vec::new(),
+            vacations: BTreeMap::new(),
+            comment_chars: COMMENTS.to_vec(),
+        }
+    }
+
+    /// When lenient, lines that cannot be used are skipped.
+    #[must_use]
+    #[inline]
+    pub fn lenient(self, lenient: bool) -> Self {
+        Self { lenient, ..self }
+    }
+
+    /// When set, a trailing clock in on a day before `now` is clocked out at
+    /// this time of its own day, or at the clock in itself when that is
+    /// later, instead of being counted up to `now`.
+    #[must_use]
+    #[inline]
+    pub fn assume_clockout(self, assume_clockout: Option<Time>) -> Self {
+        Self {
+            assume_clockout,
+            ..self
+        }
+    }
+
+    /// The markers of a comment, see [`Options::comment_chars`].
+    #[must_use]
+    #[inline]
+    pub fn comment_chars(self, comment_chars: &[char]) -> Self {
+        Self {
+            comment_chars: comment_chars.to_vec(),
+            ..self
+        }
+    }
+
+    /// When strict, a timestamp after `now` is an error instead of a warning.
+    #[must_use]
+    #[inline]
+    pub fn strict(self, strict: bool) -> Self {
+        Self { strict, ..self }
+    }
+
+    /// When set, a clock in without an account takes the one of its clock
+    /// out, see [`Options::trust_clockout_account`].
+    #[must_use]
+    #[inline]
+    pub fn trust_clockout_account(self, trust_clockout_account: bool) -> Self {
+        Self {
+            trust_clockout_account,
+            ..self
+        }
+    }
+
+    /// When set, clock outs before the first clock in are ignored, see
+    /// [`Options::skip_leading_out`].
+    #[must_use]
+    #[inline]
+    pub fn skip_leading_out(self, skip_leading_out: bool) -> Self {
+        Self {
+            skip_leading_out,
+            ..self
+        }
+    }
+
+    /// When set, a clock in while clocked in replaces the earlier one, see
+    /// [`Options::resume_latest`].
+    #[must_use]
+    #[inline]
+    pub fn resume_latest(self, resume_latest: bool) -> Self {
+        Self {
+            resume_latest,
+            ..self
+        }
+    }
+
+    /// The tokens starting a clock in and a clock out line, instead of `i`
+    /// and `o`, see [`Options::in_marker`].
+    #[must_use]
+    #[inline]
+    pub fn clock_markers(self, clock_in: &str, clock_out: &str) -> Self {
+        let clock_markers = (clock_in != "i" || clock_out != "o")
+            .then(|| (clock_in.to_owned(), clock_out.to_owned()));
+        Self {
+            clock_markers,
+            ..self
+        }
+    }
+
+    /// Returns the lines used so far that are suspect, like a timestamp
+    /// after `now`.
+    #[must_use]
+    #[inline]
+    pub fn warnings(&self) -> &[ParseError] {
+        &self.warnings
+    }
+
+    /// Returns the hours of absence per date of the `# vacation`
+    /// directives read so far.
+    #[must_use]
+    #[inline]
+    pub fn vacations(&self) -> &BTreeMap<Date, Duration> {
+        &self.vacations
+    }
+
+    /// Returns the lines skipped so far.
+    #[must_use]
+    #[inline]
+    pub fn into_skipped(self) -> Vec<ParseError> {
+        self.skipped
+    }
+
+    fn next_interval(&mut self) -> anyhow::Result<Option<Interval>> {
+        while let Some(line) = self.lines.next() {
+            self.line_number += 1;
+            let line_number = self.line_number;
+            let ip = line.with_context(|| format!("failed to read line {}", line_number))?;
+            if ip.starts_with(self.comment_chars.as_slice()) || ip.is_empty() {
+                if let Some((date, hours)) = parse_vacation(&ip, &self.comment_chars) {
+                    *self.vacations.entry(date).or_default() += hours;
+                }
+                continue;
+            }
+            let line = strip_comment(&ip, &self.comment_chars);
+            let step = self.recognize(line).and_then(|(line, shift)| {
+                self.step(&line).map_err(|error| ParseError {
+                    column: error.column
+                        + if error.column > CLOCK_TYPE_COLUMN {
+                            shift
+                        } else {
+                            0
+                        },
+                    ..error
+                })
+            });
+            match step {
+                Ok(None) => {}
+                Ok(Some(interval)) => return Ok(Some(interval)),
+                Err(error) => self.skip(ParseError {
+                    line: line_number,
+                    ..error
+                })?,
+            }
+        }
+        if self.state == States::ExpectingClockOut {
+            self.state = States::ExpectingClockIn;
+            if self.now < self.clockin {
+                self.skip(ParseError {
+                    line: self.clockin_line,
+                    column: TIMESTAMP_COLUMN,
+                    kind: ParseErrorKind::ClockInAfterNow,
+                    message: "now is before clock in time".to_owned(),
+                })?;
+                return Ok(None);
+            }
+            let assumed_end = self
+                .assume_clockout
+                .filter(|_| self.clockin.date() < self.now.date())
+                .map(|time| {
+                    max(
+                        self.clockin,
+                        PrimitiveDateTime::new(self.clockin.date(), time),
+                    )
+                });
+            return Ok(Some(Interval {
+                start: self.clockin,
+                end: assumed_end.unwrap_or(self.now),
+                account: std::mem::take(&mut self.account),
+                open: assumed_end.is_none(),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Rewrites a line starting with a configured clock marker to start with
+    /// `i` or `o`, returning it along with how many columns shorter it got.
+    fn recognize<'a>(&self, line: &'a str) -> Result<(Cow<'a, str>, usize), ParseError> {
+        let Some((clock_in, clock_out)) = &self.clock_markers else {
+            return Ok((Cow::Borrowed(line), 0));
+        };
+        let (token, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let clock_type = if token == clock_in {
+            'i'
+        } else if token == clock_out {
+            'o'
+        } else {
+            return Err(ParseError {
+                line: 0,
+                column: CLOCK_TYPE_COLUMN,
+                kind: ParseErrorKind::ClockType,
+                message: format!(
+                    "unknown clock type: [{}], expected [{}] or [{}]",
+                    token, clock_in, clock_out
+                ),
+            });
+        };
+        Ok((
+            Cow::Owned(format!("{} {}", clock_type, rest)),
+            token.len().saturating_sub(1),
+        ))
+    }
+
+    /// Records the error when lenient, returns it otherwise.
+    fn skip(&mut self, error: ParseError) -> anyhow::Result<()> {
+        if self.lenient {
+            self.skipped.push(error);
+            Ok(())
+        } else {
+            Err(error.into())
+        }
+    }
+
+    /// Advances the state machine with a line, the returned error has its
+    /// `line` left at 0.
+    fn step(&mut self, ip: &str) -> Result<Option<Interval>, ParseError> {
+        let (clock_type, time_stamp, end) = parse_line(ip)?;
+        let error = |kind, column, message: &str| ParseError {
+            line: 0,
+            column,
+            kind,
+            message: message.to_owned(),
+        };
+        let latest = end.map_or(time_stamp, |end| max(time_stamp, end));
+        if latest > self.now {
+            let future = error(
+                ParseErrorKind::Future,
+                TIMESTAMP_COLUMN,
+                &format!(
+                    "timestamp {} is after now",
+                    latest.format(TIMESTAMP_FORMAT).unwrap_or_default()
+                ),
+            );
+            if self.strict {
+                return Err(future);
+            }
+            self.warnings.push(ParseError {
+                line: self.line_number,
+                ..future
+            });
+        }
+        if clock_type == ClockType::In {
+            self.clocked_in_once = true;
+            if self.resume_latest && self.state == States::ExpectingClockOut {
+                self.warnings.push(ParseError {
+                    line: self.clockin_line,
+                    ..error(
+                        ParseErrorKind::UnexpectedClockIn,
+                        CLOCK_TYPE_COLUMN,
+                        &format!(
+                            "discarded clock in without clock out, resumed at line {}",
+                            self.line_number
+                        ),
+                    )
+                });
+                self.state = States::ExpectingClockIn;
+            }
+        }
+        match (self.state, clock_type, end) {
+            (States::ExpectingClockIn, ClockType::In, Some(end)) => {
+                if end < time_stamp {
+                    return Err(error(
+                        ParseErrorKind::ClockOutBeforeClockIn,
+                        TIMESTAMP_COLUMN,
+                        "clock out time before clock in time",
+                    ));
+                }
+                Ok(Some(Interval {
+                    start: time_stamp,
+                    end,
+                    account: parse_account(ip).to_owned(),
+                    open: false,
+                }))
+            }
+            (States::ExpectingClockIn, ClockType::In, None) => {
+                self.clockin = time_stamp;
+                self.clockin_line = self.line_number;
+                self.account = parse_account(ip).to_owned();
+                self.state = States::ExpectingClockOut;
+                Ok(None)
+            }
+            (States::ExpectingClockOut, ClockType::Out, _) => {
+                if time_stamp < self.clockin {
+                    return Err(error(
+                        ParseErrorKind::ClockOutBeforeClockIn,
+                        TIMESTAMP_COLUMN,
+                        "clock out time before clock in time",
+                    ));
+                }
+                self.state = States::ExpectingClockIn;
+                if self.account.is_empty() && self.trust_clockout_account {
+                    parse_account(ip).clone_into(&mut self.account);
+                }
+                Ok(Some(Interval {
+                    start: self.clockin,
+                    end: time_stamp,
+                    account: std::mem::take(&mut self.account),
+                    open: false,
+                }))
+            }
+            (States::ExpectingClockIn, ClockType::Out, _)
+                if self.skip_leading_out && !self.clocked_in_once =>
+            {
+                Ok(None)
+            }
+            (States::ExpectingClockIn, ClockType::Out, _) => Err(error(
+                ParseErrorKind::UnexpectedClockOut,
+                CLOCK_TYPE_COLUMN,
+                "unexpected, clock out, expecting clock in",
+            )),
+            (States::ExpectingClockOut, ClockType::In, _) => Err(error(
+                ParseErrorKind::UnexpectedClockIn,
+                CLOCK_TYPE_COLUMN,
+                "unexpected, clock in, expecting clock out",
+            )),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Intervals<R> {
+    type Item = anyhow::Result<Interval>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_interval().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.done = true;
+        }
+        next
+    }
+}
+
+fn trace_interval(trace: &mut dyn Write, interval: &Interval) -> anyhow::Result<()> {
+    writeln!(
+        trace,
+        "{} - {} {}{} {}",
+        interval.start.format(TIMESTAMP_FORMAT)?,
+        interval.end.format(TIMESTAMP_FORMAT)?,
+        hours_mins(interval.duration()),
+        if interval.open { " (open)" } else { "" },
+        interval.account
+    )
+    .context("unable to write trace")
+}
+
+fn summarize_lines(
+    reader: Box<dyn BufRead>,
+    now: &PrimitiveDateTime,
+    options: &Options,
+) -> anyhow::Result<Summary> {
+    summarize_reader(reader, now, options)
+}
+
+/// Summarizes the timelog read from `reader`. When `options.lenient` is
+/// set, the lines that could not be used end up in [`Summary::skipped`].
+#[inline]
+pub fn summarize_reader<R: BufRead>(
+    reader: R,
+    now: &PrimitiveDateTime,
+    options: &Options,
+) -> anyhow::Result<Summary> {
+    summarize_traced(reader, now, options, &mut io::stderr())
+}
+
+/// Returns the open session at the end of the timelog read from `reader`,
+/// if any, its account resolved like in [`summarize_reader`]. Reads up to
+/// the last entry but aggregates nothing along the way.
+#[inline]
+pub fn open_session<R: BufRead>(
+    reader: R,
+    now: &PrimitiveDateTime,
+    options: &Options,
+) -> anyhow::Result<Option<Interval>> {
+    let last = Intervals::new(reader, *now)
+        .comment_chars(&options.comment_chars)
+        .clock_markers(&options.in_marker, &options.out_marker)
+        .lenient(options.lenient)
+        .strict(options.strict)
+        .skip_leading_out(options.skip_leading_out)
+        .resume_latest(options.resume_latest)
+        .last()
+        .transpose()?;
+    Ok(last
+        .filter(|interval| interval.open)
+        .map(|interval| Interval {
+            account: options.resolve_account(interval.account),
+            ..interval
+        }))
+}
+
+/// Adds the time from `start` to `end` to the hours of the day it spans.
+fn add_to_hour_histogram(
+    histogram: &mut [Duration; 24],
+    start: PrimitiveDateTime,
+    end: PrimitiveDateTime,
+) {
+    let mut from = start;
+    while from < end {
+        let hour = PrimitiveDateTime::new(from.date(), Time::MIDNIGHT)
+            + Duration::hours(from.hour().into());
+        let to = min(end, hour + Duration::HOUR);
+        histogram[usize::from(from.hour())] += to - from;
+        from = to;
+    }
+}
+
+fn summarize_traced<R: BufRead>(
+    reader: R,
+    now: &PrimitiveDateTime,
+    options: &Options,
+    trace: &mut dyn Write,
+) -> anyhow::Result<Summary> {
+    let mut tally = Tally::default();
+    let mut clockin = PrimitiveDateTime::MIN;
+    let mut previous_date: Date = PrimitiveDateTime::MIN.date();
+    let mut previous_end: Option<PrimitiveDateTime> = None;
+    let mut intervals = options.intervals(reader, *now);
+    let now = &options.as_of.unwrap_or(*now);
+    let today = options.today.unwrap_or_else(|| now.date());
+    for interval in intervals.by_ref() {
+        let interval = interval?;
+        if options.verbose {
+            let account = options.resolve_account(interval.account.clone());
+            trace_interval(
+                trace,
+                &Interval {
+                    account,
+                    ..interval.clone()
+                },
+            )?;
+        }
+        let Some(interval) = options.select(interval) else {
+            continue;
+        };
+        let current_date = interval.start.date();
+        if previous_date != current_date {
+            tally.worked_today = Duration::ZERO;
+            tally.num_days_worked += 1;
+            previous_date = current_date;
+        }
+        if current_date == today {
+            // The earliest clock in of today, rather than the first one read,
+            // in case lines of today got out of order.
+            tally.first_punchin_today = Some(
+                tally
+                    .first_punchin_today
+                    .map_or(interval.start, |first| min(first, interval.start)),
+            );
+            if let Some(end) = previous_end.filter(|end| end.date() == today) {
+                tally.break_taken_today |= interval.start - end > BREAK_THRESHOLD;
+            }
+            tally.last_end_today = Some(interval.end);
+        }
+        clockin = interval.start;
+        if let Some(lpi) = tally.last_punchin {
+            tally.last_punchin = Some(max(lpi, interval.start));
+        } else {
+            tally.last_punchin = Some(interval.start);
+        }
+        if !interval.open {
+            if let Some(lpa) = tally.last_punchout {
+                tally.last_punchout = Some(max(lpa, interval.end))
+            } else {
+                tally.last_punchout = Some(interval.end);
+            }
+        }
+        let mut clocked = options.elapsed(&interval)?;
+        let project = if interval.account.is_empty() {
+            UNASSIGNED
+        } else {
+            &interval.account
+        };
+        let day = tally.days.entry(current_date).or_insert_with(|| Day {
+            first_punchin: interval.start,
+            last_end: interval.end,
+            worked: Duration::ZERO,
+            projects: BTreeMap::new(),
+        });
+        day.first_punchin = min(day.first_punchin, interval.start);
+        day.last_end = max(day.last_end, interval.end);
+        if options.cap_daily {
+            let target = options.target_on(current_date);
+            let counted = min(clocked, max(Duration::ZERO, target - day.worked));
+            tally.uncounted_overtime += clocked - counted;
+            clocked = counted;
+        }
+        day.worked += clocked;
+        *day.projects.entry(project.to_owned()).or_default() += clocked;
+        tally.worked_today += clocked;
+        tally.total_worked += clocked;
+        if !interval.open {
+            tally.recorded_total += clocked;
+        }
+        if !(options.merge_adjacent && previous_end == Some(interval.start)) {
+            tally.num_sessions += 1;
+        }
+        if options.is_billable(&interval.account) {
+            tally.billable += clocked;
+        } else {
+            tally.non_billable += clocked;
+        }
+        tally.clocked_in = interval.open;
+        *tally.projects.entry(project.to_owned()).or_default() += clocked;
+        add_to_hour_histogram(
+            &mut tally.hour_histogram,
+            interval.start,
+            interval.start + clocked,
+        );
+        tally
+            .last_worked
+            .entry(project.to_owned())
+            .and_modify(|date| *date = max(*date, current_date))
+            .or_insert(current_date);
+        previous_end = Some(interval.end);
+    }
+    tally
+        .warnings
+        .extend(intervals.warnings().iter().map(Warning::from));
+    tally.vacations = intervals.vacations().clone();
+    tally.skipped = intervals.into_skipped();
+    if options.today.is_some() {
+        tally.worked_today = tally
+            .days
+            .get(&today)
+            .map_or(Duration::ZERO, |day| day.worked);
+    } else if clockin.date() != now.date() {
+        tally.worked_today = Duration::ZERO;
+    }
+    let summary = Summary::new(tally, now, options);
+    Ok(summary)
+}
+
+#[inline]
+pub fn summarize_file<P>(
+    filename: P,
+    now: &PrimitiveDateTime,
+    options: &Options,
+) -> anyhow::Result<Summary>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(&filename)
+        .with_context(|| format!("unable to read {}", &filename.as_ref().to_string_lossy()))?;
+    summarize_lines(
+        Box::new(io::BufReader::with_capacity(512 * 1024, file)),
+        now,
+        options,
+    )
+    .map_err(|error| not_a_timelog(error, filename.as_ref()))
+}
+
+/// Like [`summarize_file`], calling `progress` with the number of bytes
+/// read so far and the size of the file as it is read.
+#[inline]
+pub fn summarize_file_with_progress<P, F>(
+    filename: P,
+    now: &PrimitiveDateTime,
+    options: &Options,
+    mut progress: F,
+) -> anyhow::Result<Summary>
+where
+    P: AsRef<Path>,
+    F: FnMut(u64, u64),
+{
+    let file = File::open(&filename)
+        .with_context(|| format!("unable to read {}", &filename.as_ref().to_string_lossy()))?;
+    let size = file.metadata().map_or(0, |metadata| metadata.len());
+    let reader = Progress::new(io::BufReader::with_capacity(512 * 1024, file), |read| {
+        progress(read, size);
+    });
+    summarize_reader(reader, now, options).map_err(|error| not_a_timelog(error, filename.as_ref()))
+}
+
+/// Adds the file name to an error caused by reading something that is not
+/// UTF-8.
+fn not_a_timelog(error: anyhow::Error, filename: &Path) -> anyhow::Error {
+    if is_invalid_data(&error) {
+        error.context(format!(
+            "{} does not look like a text timelog",
+            filename.to_string_lossy()
+        ))
+    } else {
+        error
+    }
+}
+
+/// A reader calling a callback with the total number of bytes consumed
+/// every time more of `R` is consumed.
+pub struct Progress<R, F> {
+    inner: R,
+    consumed: u64,
+    callback: F,
+}
+
+impl<R, F: FnMut(u64)> Progress<R, F> {
+    #[must_use]
+    #[inline]
+    pub fn new(inner: R, callback: F) -> Self {
+        Self {
+            inner,
+            consumed: 0,
+            callback,
+        }
+    }
+
+    fn advance(&mut self, amount: usize) {
+        if amount > 0 {
+            self.consumed += amount as u64;
+            (self.callback)(self.consumed);
+        }
+    }
+}
+
+impl<R: Read, F: FnMut(u64)> Read for Progress<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.advance(read);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead, F: FnMut(u64)> BufRead for Progress<R, F> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+        self.advance(amount);
+    }
+}
+
+/// Summarizes the timelogs at `paths` as one, see [`read_files`].
+#[inline]
+pub fn summarize_files<P: AsRef<Path>>(
+    paths: &[P],
+    now: &PrimitiveDateTime,
+    options: &Options,
+) -> anyhow::Result<Summary> {
+    let (contents, warnings) = read_files(paths, options)?;
+    let mut summary = summarize_reader(io::Cursor::new(contents), now, options)?;
+    summary
+        .warnings
+        .extend(warnings.into_iter().map(Warning::from));
+    Ok(summary)
+}
+
+/// Reads the timelogs at `paths` as one, in the given order so a session
+/// may be clocked out in the next file. A file that cannot be read, is not
+/// text or has a malformed line is skipped with a warning, which is returned
+/// along with the contents, unless [`Options::strict`] is set. Malformed
+/// lines are left to the parser when [`Options::lenient`] is set.
+#[inline]
+pub fn read_files<P: AsRef<Path>>(
+    paths: &[P],
+    options: &Options,
+) -> anyhow::Result<(String, Vec<String>)> {
+    let mut contents = String::new();
+    let mut warnings = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let file = std::fs::read_to_string(path).map_err(anyhow::Error::from);
+        let file = if options.lenient {
+            file
+        } else {
+            file.and_then(|file| match malformed_line(&file, options) {
+                Some(error) => Err(error.into()),
+                None => Ok(file),
+            })
+        };
+        match file {
+            Ok(file) => {
+                contents.push_str(&file);
+                if !contents.ends_with('\n') {
+                    contents.push('\n');
+                }
+            }
+            Err(error) if options.strict => {
+                return Err(error)
+                    .with_context(|| format!("unable to read {}", path.to_string_lossy()));
+            }
+            Err(error) => {
+                warnings.push(format!("skipped {}: {}", path.to_string_lossy(), error));
+            }
+        }
+    }
+    Ok((contents, warnings))
+}
+
+/// The first line of a timelog's `contents` that does not parse, on its own,
+/// with the comment characters and the clock markers of `options`.
+fn malformed_line(contents: &str, options: &Options) -> Option<ParseError> {
+    let markers = Intervals::new(io::empty(), PrimitiveDateTime::MAX)
+        .clock_markers(&options.in_marker, &options.out_marker);
+    contents.lines().enumerate().find_map(|(i, line)| {
+        if line.is_empty() || line.starts_with(options.comment_chars.as_slice()) {
+            return None;
+        }
+        markers
+            .recognize(strip_comment(line, &options.comment_chars))
+            .and_then(|(line, _)| parse_line(&line))
+            .err()
+            .map(|error| ParseError {
+                line: i + 1,
+                ..error
+            })
+    })
+}
+
+/// The files in `dir` whose name matches the glob `pattern`, sorted by name,
+/// which is chronological for names like `timelog-2022-04.txt`.
+#[inline]
+pub fn timelog_files(dir: &Path, pattern: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("unable to read directory {}", dir.to_string_lossy()))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let matched = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| glob::matches(pattern, name));
+        if matched && entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether the error was caused by reading something that is not UTF-8.
+fn is_invalid_data(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|error| error.kind() == io::ErrorKind::InvalidData)
+    })
+}
+
+/// Returns the time worked per date. An interval is attributed entirely to
+/// the date it started on, also when it ends on a later date. A trailing
+/// clock in without a clock out is not included since, unlike
+/// [`summarize_file`], there is no `now` to measure it against.
+#[inline]
+pub fn daily_map(reader: impl BufRead) -> anyhow::Result<BTreeMap<Date, Duration>> {
+    let mut days = BTreeMap::new();
+    for interval in Intervals::new(reader, PrimitiveDateTime::MAX) {
+        let interval = interval?;
+        if !interval.open {
+            *days.entry(interval.start.date()).or_default() += interval.duration();
+        }
+    }
+    Ok(days)
+}
+
+/// Drops the seconds and any fraction of them, e.g. for a projected time
+/// to leave that carries the seconds of `now`.
+#[must_use]
+#[inline]
+pub fn truncate_to_minute(date_time: PrimitiveDateTime) -> PrimitiveDateTime {
+    date_time.replace_time(
+        Time::from_hms(date_time.hour(), date_time.minute(), 0).unwrap_or(date_time.time()),
+    )
+}
+
+#[inline]
+pub fn format_time(date_time: PrimitiveDateTime) -> anyhow::Result<String> {
+    date_time
+        .time()
+        .format(HOUR_MINUTE_FORMAT)
+        .context("unable to format time")
+}
+
+#[inline]
+pub fn format_date_time(date_time: PrimitiveDateTime) -> anyhow::Result<String> {
+    date_time
+        .format(DATE_TIME_FORMAT)
+        .context("unable to format time")
+}
+
+#[must_use]
+#[inline]
+pub fn hours_mins(duration: Duration) -> String {
+    let hours = duration.whole_hours();
+    let minutes = (duration - Duration::hours(hours)).whole_minutes();
+    if hours == 0 && minutes.is_negative() {
+        format!("-{: <4} hours, {: <5} minutes", hours, i64::abs(minutes))
+    } else {
+        format!("{: <5} hours, {: <5} minutes", hours, i64::abs(minutes))
+    }
+}
+
+/// Like [`hours_mins`] followed by the seconds, e.g.
+/// `8     hours, 0     minutes, 32    seconds`. A negative duration has
+/// its minus on the first unit that is not zero.
+#[must_use]
+#[inline]
+pub fn hours_mins_secs(duration: Duration) -> String {
+    let sign = if duration.is_negative() { "-" } else { "" };
+    let duration = duration.abs();
+    let hours = duration.whole_hours();
+    let minutes = (duration - Duration::hours(hours)).whole_minutes();
+    let seconds = (duration - Duration::hours(hours) - Duration::minutes(minutes)).whole_seconds();
+    let (hours_sign, minutes_sign, seconds_sign) = match (hours, minutes) {
+        (0, 0) => ("", "", sign),
+        (0, _) => ("", sign, ""),
+        _ => (sign, "", ""),
+    };
+    format!(
+        "{: <5} hours, {: <5} minutes, {: <5} seconds",
+        format!("{}{}", hours_sign, hours),
+        format!("{}{}", minutes_sign, minutes),
+        format!("{}{}", seconds_sign, seconds)
+    )
+}
+
+/// How a negative duration is marked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NegativeStyle {
+    /// A leading minus, e.g. `-5    hours, 16    minutes`.
+    #[default]
+    Minus,
+    /// Accounting style parentheses, e.g. `(5     hours, 16    minutes)`.
+    /// Other durations are padded by a space on both sides to stay aligned.
+    Parentheses,
+}
+
+impl FromStr for NegativeStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minus" => Ok(Self::Minus),
+            "paren" => Ok(Self::Parentheses),
+            other => Err(anyhow!(
+                "unknown negative style: [{}], expected minus or paren",
+                other
+            )),
+        }
+    }
+}
+
+impl NegativeStyle {
+    /// Renders `duration` using `render`, marking it in this style when it
+    /// is negative.
+    #[must_use]
+    pub fn render(self, duration: Duration, render: impl Fn(Duration) -> String) -> String {
+        match self {
+            Self::Minus => render(duration),
+            Self::Parentheses if duration.is_negative() => format!("({})", render(-duration)),
+            Self::Parentheses => format!(" {} ", render(duration)),
+        }
+    }
+}
+
+/// Like [`hours_mins`], marking a negative duration in `style`.
+#[must_use]
+#[inline]
+pub fn hours_mins_styled(duration: Duration, style: NegativeStyle) -> String {
+    style.render(duration, hours_mins)
+}
+
+/// Like [`hours_mins`], with the digits of the hours grouped per thousand
+/// using `separator`, e.g. `1,205 hours`.
+#[must_use]
+#[inline]
+pub fn hours_mins_grouped(duration: Duration, separator: char) -> String {
+    let hours = duration.whole_hours();
+    let minutes = (duration - Duration::hours(hours)).whole_minutes();
+    let digits = hours.unsigned_abs().to_string();
+    let mut grouped = String::with_capacity(digits.len() * 4 / 3 + 1);
+    if hours.is_negative() || minutes.is_negative() {
+        grouped.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    format!("{: <5} hours, {: <5} minutes", grouped, i64::abs(minutes))
+}
+
+/// Renders the duration tersely as hours and minutes, e.g. `8h 0m` or
+/// `-5h 16m`.
+#[must_use]
+#[inline]
+pub fn compact_hours_mins(duration: Duration) -> String {
+    let sign = if duration.is_negative() { "-" } else { "" };
+    let duration = duration.abs();
+    let hours = duration.whole_hours();
+    let minutes = (duration - Duration::hours(hours)).whole_minutes();
+    format!("{}{}h {}m", sign, hours, minutes)
+}
+
+/// Renders the duration as a decimal number of hours, e.g. `8.50`.
+#[must_use]
+#[inline]
+pub fn decimal_hours(duration: Duration) -> String {
+    format!("{:.2}", duration.as_seconds_f64() / 3600.0)
+}
+
+/// Renders the duration like [`decimal_hours`] with `separator` as the
+/// decimal separator, e.g. `8,50`.
+#[must_use]
+#[inline]
+pub fn decimal_hours_with(duration: Duration, separator: char) -> String {
+    decimal_hours(duration).replace('.', separator.encode_utf8(&mut [0; 4]))
+}
+
+/// The renderings of a duration used by `tlsum`, for reuse by other front
+/// ends. Negative durations are rendered with a leading minus, combine with
+/// [`NegativeStyle::render`] for other styles.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DurationFormat {
+    /// Hours and minutes aligned in columns, see [`hours_mins`].
+    ///
+    /// ```
+    /// use lib::DurationFormat;
+    /// use time::Duration;
+    ///
+    /// let format = DurationFormat::HoursMins;
+    /// assert_eq!(format.format(Duration::minutes(316)), "5     hours, 16    minutes");
+    /// assert_eq!(format.format(Duration::minutes(-316)), "-5    hours, 16    minutes");
+    /// ```
+    #[default]
+    HoursMins,
+    /// Like `HoursMins` with the digits of the hours grouped per thousand
+    /// using the separator, see [`hours_mins_grouped`].
+    ///
+    /// ```
+    /// use lib::DurationFormat;
+    /// use time::Duration;
+    ///
+    /// let format = DurationFormat::Grouped(',');
+    /// assert_eq!(format.format(Duration::hours(1205)), "1,205 hours, 0     minutes");
+    /// ```
+    Grouped(char),
+    /// Terse hours and minutes, see [`compact_hours_mins`].
+    ///
+    /// ```
+    /// use lib::DurationFormat;
+    /// use time::Duration;
+    ///
+    /// assert_eq!(DurationFormat::Compact.format(Duration::minutes(-316)), "-5h 16m");
+    /// ```
+    Compact,
+    /// Like `HoursMins` followed by the seconds, see [`hours_mins_secs`].
+    ///
+    /// ```
+    /// use lib::DurationFormat;
+    /// use time::Duration;
+    ///
+    /// let format = DurationFormat::WithSeconds;
+    /// assert_eq!(
+    ///     format.format(Duration::seconds(-(8 * 3600 + 32))),
+    ///     "-8    hours, 0     minutes, 32    seconds"
+    /// );
+    /// ```
+    WithSeconds,
+    /// A decimal number of hours, see [`decimal_hours`].
+    ///
+    /// ```
+    /// use lib::DurationFormat;
+    /// use time::Duration;
+    ///
+    /// assert_eq!(DurationFormat::Decimal.format(Duration::minutes(510)), "8.50");
+    /// ```
+    Decimal,
+}
+
+impl DurationFormat {
+    /// Renders `duration` in this format.
+    #[must_use]
+    #[inline]
+    pub fn format(self, duration: Duration) -> String {
+        match self {
+            Self::HoursMins => hours_mins(duration),
+            Self::Grouped(separator) => hours_mins_grouped(duration, separator),
+            Self::Compact => compact_hours_mins(duration),
+            Self::WithSeconds => hours_mins_secs(duration),
+            Self::Decimal => decimal_hours(duration),
+        }
+    }
+}
+
+/// Returns the last part of an account, the part after the last `:`, which
+/// names the person in accounts like `e:fc:fred`.
+#[must_use]
+#[inline]
+pub fn account_owner(account: &str) -> &str {
+    account.rsplit(':').next().unwrap_or(account)
+}
+
+/// Returns the top-level part of an account, the part up to the first `:`.
+#[must_use]
+#[inline]
+pub fn top_level_account(account: &str) -> &str {
+    account.split(':').next().unwrap_or(account)
+}
+
+/// Sums the time worked per account into groups keyed by what `pattern`
+/// captures from the account, e.g. `^(?P<client>[^:]+):` groups by client.
+/// The key is the first capture group that took part in the match, or the
+/// whole match for a pattern without groups. Accounts that don't match end
+/// up in [`UNMATCHED`].
+#[must_use]
+pub fn rollup_by_regex(summary: &Summary, pattern: &Regex) -> BTreeMap<String, Duration> {
+    let mut groups = BTreeMap::new();
+    for (account, worked) in &summary.projects {
+        let key = pattern.captures(account).map_or(UNMATCHED, |captures| {
+            captures
+                .iter()
+                .skip(1)
+                .flatten()
+                .chain(captures.get(0))
+                .next()
+                .map_or(UNMATCHED, |group| group.as_str())
+        });
+        *groups.entry(key.to_owned()).or_insert(Duration::ZERO) += *worked;
+    }
+    groups
+}
+
+fn is_weekend(date: Date) -> bool {
+    matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday)
+}
+
+/// Whether a day after `from` and before `to` was not worked, a weekend
+/// day only when not `skip_weekends`.
+fn breaks_streak(from: Date, to: Date, skip_weekends: bool) -> bool {
+    let mut date = from;
+    while let Some(next) = date.next_day().filter(|next| *next < to) {
+        if !(skip_weekends && is_weekend(next)) {
+            return true;
+        }
+        date = next;
+    }
+    false
+}
+
+/// The current and the longest streak of the ascending worked `dates`, see
+/// [`Summary::current_streak`].
+fn streaks(dates: impl Iterator<Item = Date>, today: Date, skip_weekends: bool) -> (u32, u32) {
+    let mut last: Option<Date> = None;
+    let mut streak = 0_u32;
+    let mut longest = 0_u32;
+    for date in dates {
+        streak = match last {
+            Some(last) if !breaks_streak(last, date, skip_weekends) => streak + 1,
+            _ => 1,
+        };
+        longest = max(longest, streak);
+        last = Some(date);
+    }
+    let current = match last {
+        Some(last) if last <= today && !breaks_streak(last, today, skip_weekends) => streak,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+/// The number of weekdays from the Monday of the week of `today` up to and
+/// including `today`, e.g. 5 on a Saturday or a Sunday. The workdays are
+/// always Monday to Friday, they are not configurable.
+#[must_use]
+#[inline]
+pub fn workdays_this_week(today: Date) -> u32 {
+    min(today.weekday().number_from_monday(), 5).into()
+}
+
+/// The number of weekdays after `today` up to and including the last day of
+/// its month.
+fn remaining_workdays(today: Date) -> u32 {
+    let mut remaining = 0;
+    let mut date = today;
+    while let Some(next) = date.next_day().filter(|next| next.month() == today.month()) {
+        if !is_weekend(next) {
+            remaining += 1;
+        }
+        date = next;
+    }
+    remaining
+}
+
+/// Labels `date` relative to `today`: "Today", "Yesterday", the weekday name
+/// within the last week and the ISO date otherwise, e.g. `2022-01-03`.
+#[must_use]
+pub fn relative_date_label(date: Date, today: Date) -> String {
+    match (today - date).whole_days() {
+        0 => "Today".to_owned(),
+        1 => "Yesterday".to_owned(),
+        2..=6 => date.weekday().to_string(),
+        _ => date.to_string(),
+    }
+}
+
+/// Returns the last moment of the day before `now`.
+#[must_use]
+#[inline]
+pub fn end_of_yesterday(now: &PrimitiveDateTime) -> PrimitiveDateTime {
+    PrimitiveDateTime::new(now.date(), Time::MIDNIGHT) - Duration::nanoseconds(1)
+}
+
+#[inline]
+pub fn now() -> anyhow::Result<PrimitiveDateTime> {
+    let local = OffsetDateTime::now_local()?;
+    let now = PrimitiveDateTime::new(local.date(), local.time());
+    Ok(now)
+}
+
+/// Returns the current time at `offset` instead of the local offset, for
+/// consistent reports on hosts in another time zone.
+#[must_use]
+#[inline]
+pub fn now_at(offset: UtcOffset) -> PrimitiveDateTime {
+    at_offset(OffsetDateTime::now_utc(), offset)
+}
+
+fn at_offset(instant: OffsetDateTime, offset: UtcOffset) -> PrimitiveDateTime {
+    let local = instant.to_offset(offset);
+    PrimitiveDateTime::new(local.date(), local.time())
+}
+
+/// Parses a date like `2022-01-05`.
+#[inline]
+pub fn parse_date(date: &str) -> anyhow::Result<Date> {
+    Date::parse(date, DATE_INPUT_FORMAT)
+        .with_context(|| format!("expected a date as YYYY-MM-DD, got: [{}]", date))
+}
+
+/// Parses a month as its number, e.g. `4`, or its English name, e.g.
+/// `april` or `apr`.
+#[inline]
+pub fn parse_month(month: &str) -> anyhow::Result<Month> {
+    if let Ok(number) = month.parse::<u8>() {
+        return Month::try_from(number)
+            .with_context(|| format!("expected a month in 1..=12, got: [{}]", month));
+    }
+    let name = month.to_lowercase();
+    let mut candidate = Month::January;
+    for _ in 0..12 {
+        let full = candidate.to_string().to_lowercase();
+        if name.len() >= 3 && full.starts_with(&name) {
+            return Ok(candidate);
+        }
+        candidate = candidate.next();
+    }
+    bail!("expected a month as a number or a name, got: [{}]", month)
+}
+
+/// The year the fiscal year of `date` starts in, fiscal years starting on
+/// the first of `start`, e.g. March 2023 is in fiscal year 2022 when fiscal
+/// years start in April.
+#[must_use]
+#[inline]
+pub fn fiscal_year(date: Date, start: Month) -> i32 {
+    if (date.month() as u8) < (start as u8) {
+        date.year() - 1
+    } else {
+        date.year()
+    }
+}
+
+/// Reads a file of dates, one `YYYY-MM-DD` per line, skipping blank lines
+/// and lines starting with `#`.
+#[inline]
+pub fn read_dates(path: &Path) -> anyhow::Result<BTreeSet<Date>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read {}", path.to_string_lossy()))?;
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_date)
+        .collect::<anyhow::Result<_>>()
+        .with_context(|| format!("invalid {}", path.to_string_lossy()))
+}
+
+/// Reads a schedule of target hours, a line like `2022-06-01,6` per date
+/// the hours take effect on, skipping blank lines and lines starting with
+/// `#`, see [`Options::schedule`].
+#[inline]
+pub fn read_schedule(path: &Path) -> anyhow::Result<BTreeMap<Date, Duration>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read {}", path.to_string_lossy()))?;
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_schedule_line)
+        .collect::<anyhow::Result<_>>()
+        .with_context(|| format!("invalid {}", path.to_string_lossy()))
+}
+
+fn parse_schedule_line(line: &str) -> anyhow::Result<(Date, Duration)> {
+    let (date, hours) = line
+        .split_once(',')
+        .with_context(|| format!("expected YYYY-MM-DD,hours, got: [{}]", line))?;
+    let hours = hours.trim();
+    let parsed: f64 = hours
+        .parse()
+        .with_context(|| format!("expected a number of hours, got: [{}]", hours))?;
+    if !parsed.is_finite() || !(0.0..=24.0).contains(&parsed) {
+        bail!("expected a number of hours in [0, 24], got: [{}]", hours);
+    }
+    Ok((
+        parse_date(date.trim())?,
+        Duration::seconds_f64(parsed * 3600.0),
+    ))
+}
+
+/// Parses an offset from UTC like `+02:00`.
+#[inline]
+pub fn parse_utc_offset(offset: &str) -> anyhow::Result<UtcOffset> {
+    UtcOffset::parse(offset, UTC_OFFSET_INPUT_FORMAT)
+        .with_context(|| format!("expected an offset as +HH:MM, got: [{}]", offset))
+}
+
+/// Parses a time of day formatted as `HH:MM`.
+#[inline]
+pub fn parse_hour_minute(time: &str) -> anyhow::Result<Time> {
+    Time::parse(time, HOUR_MINUTE_INPUT_FORMAT)
+        .with_context(|| format!("expected a time as HH:MM, got: [{}]", time))
+}
+
+/// Parses a timestamp in the first of the formats accepted in a timelog
+/// that fits, e.g. `2022/04/22 21:33:23`.
+#[inline]
+pub fn parse_timestamp(date_time: &str) -> anyhow::Result<PrimitiveDateTime> {
+    let fractional = date_time.contains('.');
+    TIMESTAMP_INPUT_FORMATS
+        .iter()
+        .find_map(|(_, whole, fraction)| {
+            let format = if fractional { fraction } else { whole };
+            PrimitiveDateTime::parse(date_time, format).ok()
+        })
+        .ok_or_else(|| {
+            let names: Vec<&str> = TIMESTAMP_INPUT_FORMATS
+                .iter()
+                .map(|(name, _, _)| *name)
+                .collect();
+            anyhow!(
+                "expected one of {}, optionally with fractional seconds",
+                names.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod parse_timestamp {
+        use super::*;
+        use time::Month;
+
+        #[test]
+        fn should_parse_to_primitive_datetime() {
+            let result = parse_timestamp("2022/04/22 21:33:23").unwrap();
+            assert_eq!(2022, result.year());
+            assert_eq!(Month::April, result.month());
+            assert_eq!(22, result.day());
+            assert_eq!((21, 33, 23), result.as_hms());
+        }
+
+        #[test]
+        fn should_parse_fractional_seconds() {
+            let result = parse_timestamp("2022/04/22 21:33:23.5").unwrap();
+            assert_eq!((21, 33, 23, 500), result.as_hms_milli());
+            let result = parse_timestamp("2022/04/22 21:33:23.250").unwrap();
+            assert_eq!((21, 33, 23, 250), result.as_hms_milli());
+            assert!(parse_timestamp("2022/04/22 21:33:23.").is_err());
+        }
+
+        #[test]
+        fn should_parse_dashed_dates() {
+            let result = parse_timestamp("2022-04-22 21:33:23").unwrap();
+            assert_eq!(result, parse_timestamp("2022/04/22 21:33:23").unwrap());
+            let result = parse_timestamp("2022-04-22 21:33:23.5").unwrap();
+            assert_eq!((21, 33, 23, 500), result.as_hms_milli());
+        }
+
+        #[test]
+        fn should_list_attempted_formats() {
+            let error = parse_timestamp("22.04.2022 21:33").unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "expected one of YYYY/MM/DD HH:MM:SS, YYYY-MM-DD HH:MM:SS, \
+                 optionally with fractional seconds"
+            );
+            assert!(parse_timestamp("2022/04-22 21:33:23").is_err());
         }
     }
 
@@ -307,183 +2809,2361 @@ mod tests {
         use time::macros::datetime;
 
         #[test]
-        fn should_parse_clock_in_line() {
-            let line = "i 2022/04/22 21:33:23 e:fc:fred";
-            let (clock_type, date_time) = parse_line(line).unwrap();
-            assert_eq!(ClockType::In, clock_type);
-            assert_eq!(datetime!(2022 - 04 - 22 21:33:23), date_time);
+        fn should_parse_clock_in_line() {
+            let line = "i 2022/04/22 21:33:23 e:fc:fred";
+            let (clock_type, date_time, end) = parse_line(line).unwrap();
+            assert_eq!(ClockType::In, clock_type);
+            assert_eq!(datetime!(2022 - 04 - 22 21:33:23), date_time);
+            assert_eq!(end, None);
+        }
+
+        #[test]
+        fn should_parse_clock_out_line() {
+            let line = "o 2022/04/22 21:33:33";
+            let (clock_type, date_time, _) = parse_line(line).unwrap();
+            assert_eq!(ClockType::Out, clock_type);
+            assert_eq!(datetime!(2022 - 04 - 22 21:33:33), date_time);
+        }
+
+        #[test]
+        fn should_split_account_after_fractional_seconds() {
+            let line = "i 2022/04/22 21:33:23.500 e:fc:fred";
+            let (_, date_time, _) = parse_line(line).unwrap();
+            assert_eq!(datetime!(2022 - 04 - 22 21:33:23.5), date_time);
+            assert_eq!(parse_account(line), "e:fc:fred");
+            assert_eq!(
+                parse_account("i 2022/04/22 21:33:23  spaced out "),
+                "spaced out"
+            );
+        }
+
+        #[test]
+        fn should_parse_epoch_seconds() {
+            let line = "i 1650656003 acme";
+            let (clock_type, date_time, _) = parse_line(line).unwrap();
+            assert_eq!(ClockType::In, clock_type);
+            assert_eq!(parse_epoch("1650656003"), Some(date_time));
+            assert_eq!(parse_account(line), "acme");
+            assert_eq!(parse_account("o 1650656003"), "");
+        }
+
+        #[test]
+        fn should_convert_epoch_seconds_to_local_time() {
+            let utc = TimeZone::utc();
+            assert_eq!(
+                epoch_to_local(1650656003, &utc),
+                Some(datetime!(2022 - 04 - 22 19:33:23))
+            );
+            let amsterdam = TimeZone::from_posix_tz("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+            assert_eq!(
+                epoch_to_local(1650656003, &amsterdam),
+                Some(datetime!(2022 - 04 - 22 21:33:23))
+            );
+        }
+
+        #[test]
+        fn should_reject_malformed_timestamp() {
+            assert!(parse_line("i 2022/04/22 21:3x:33 acme").is_err());
+            assert!(parse_line("i 16506x6003 acme").is_err());
+            assert!(parse_line("o").is_err());
+        }
+
+        #[test]
+        fn should_parse_compact_line() {
+            let line = "i 2022/04/22 09:00:00 - 2022/04/22 17:00:00 acme";
+            let (clock_type, date_time, end) = parse_line(line).unwrap();
+            assert_eq!(ClockType::In, clock_type);
+            assert_eq!(datetime!(2022 - 04 - 22 09:00:00), date_time);
+            assert_eq!(end, Some(datetime!(2022 - 04 - 22 17:00:00)));
+            assert_eq!(parse_account(line), "acme");
+            assert_eq!(parse_account("i 2022/04/22 09:00:00 - lunch"), "- lunch");
+            let error = parse_line("i 2022/04/22 09:00:00 - 2022/04/22 17:x0:00").unwrap_err();
+            assert_eq!(error.column, 25);
+        }
+
+        #[test]
+        fn should_point_at_compact_end_between_extra_spaces() {
+            let line = "i 2022/04/22 09:00:00   -   2022/04/22 17:00:00  acme ";
+            let (_, _, end) = parse_line(line).unwrap();
+            assert_eq!(end, Some(datetime!(2022 - 04 - 22 17:00:00)));
+            assert_eq!(parse_account(line), "acme");
+            let error =
+                parse_line("i 2022/04/22 09:00:00   -   2022/04/22 17:x0:00  ").unwrap_err();
+            assert_eq!(error.column, 29);
+        }
+    }
+
+    mod fiscal_year {
+        use super::*;
+        use std::io::Cursor;
+        use time::macros::{date, datetime};
+
+        #[test]
+        fn april_start_places_march_in_prior_fiscal_year() {
+            assert_eq!(fiscal_year(date!(2023 - 03 - 31), Month::April), 2022);
+            assert_eq!(fiscal_year(date!(2023 - 04 - 01), Month::April), 2023);
+            assert_eq!(fiscal_year(date!(2023 - 03 - 31), Month::January), 2023);
+
+            let log = "i 2023/03/31 09:00:00 acme\no 2023/03/31 17:00:00\n\
+                       i 2023/04/03 09:00:00 acme\no 2023/04/03 13:00:00\n";
+            let now = datetime!(2023 - 04 - 04 12:00:00);
+            let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+            assert_eq!(
+                summary.yearly_totals(Month::April),
+                BTreeMap::from([(2022, Duration::hours(8)), (2023, Duration::hours(4))])
+            );
+            assert_eq!(
+                summary.yearly_totals(Month::January),
+                BTreeMap::from([(2023, Duration::hours(12))])
+            );
+        }
+
+        #[test]
+        fn should_parse_month_by_number_or_name() {
+            assert_eq!(parse_month("4").unwrap(), Month::April);
+            assert_eq!(parse_month("April").unwrap(), Month::April);
+            assert_eq!(parse_month("sep").unwrap(), Month::September);
+            assert!(parse_month("13").is_err());
+            assert!(parse_month("ju").is_err());
+        }
+    }
+
+    mod round_mode {
+        use super::*;
+
+        const HALFWAY: Duration = Duration::seconds(7 * 60 + 30);
+        const QUARTER: Duration = Duration::minutes(15);
+
+        #[test]
+        fn should_break_ties_per_mode() {
+            assert_eq!(RoundMode::Up.round(HALFWAY, QUARTER), QUARTER);
+            assert_eq!(RoundMode::Down.round(HALFWAY, QUARTER), Duration::ZERO);
+            assert_eq!(RoundMode::Nearest.round(HALFWAY, QUARTER), QUARTER);
+            assert_eq!(RoundMode::Bankers.round(HALFWAY, QUARTER), Duration::ZERO);
+            let odd_halfway = QUARTER + HALFWAY;
+            let half_hour = Duration::minutes(30);
+            assert_eq!(RoundMode::Bankers.round(odd_halfway, QUARTER), half_hour);
+        }
+
+        #[test]
+        fn should_round_other_values_per_mode() {
+            let d = Duration::minutes(20);
+            assert_eq!(RoundMode::Up.round(d, QUARTER), Duration::minutes(30));
+            assert_eq!(RoundMode::Down.round(d, QUARTER), QUARTER);
+            assert_eq!(RoundMode::Nearest.round(d, QUARTER), QUARTER);
+            assert_eq!(RoundMode::Bankers.round(d, QUARTER), QUARTER);
+            assert_eq!(RoundMode::Up.round(QUARTER, QUARTER), QUARTER);
+        }
+
+        #[test]
+        fn should_parse_mode() {
+            assert_eq!("bankers".parse::<RoundMode>().unwrap(), RoundMode::Bankers);
+            assert!("sideways".parse::<RoundMode>().is_err());
+        }
+    }
+
+    mod strip_comment {
+        use super::*;
+
+        #[test]
+        fn should_strip_inline_comment() {
+            assert_eq!(
+                strip_comment("i 2022/04/22 21:33:23 acme ; standup", &COMMENTS),
+                "i 2022/04/22 21:33:23 acme"
+            );
+            assert_eq!(
+                strip_comment("o 2022/04/22 21:33:23\t# late", &COMMENTS),
+                "o 2022/04/22 21:33:23"
+            );
+        }
+
+        #[test]
+        fn should_keep_markers_within_account() {
+            let line = "i 2022/04/22 21:33:23 acme:ticket#12;b";
+            assert_eq!(strip_comment(line, &COMMENTS), line);
+        }
+
+        #[test]
+        fn should_strip_configured_marker_only() {
+            let line = "i 2022/04/22 21:33:23 acme % standup ; late";
+            assert_eq!(strip_comment(line, &['%']), "i 2022/04/22 21:33:23 acme");
+            assert_eq!(strip_comment(line, &[]), line);
+        }
+    }
+
+    mod hours_mins {
+        use super::*;
+
+        #[test]
+        fn should_not_wrap_at_24_hours() {
+            assert_eq!(
+                hours_mins(Duration::hours(205) + Duration::minutes(36)),
+                "205   hours, 36    minutes"
+            );
+        }
+
+        #[test]
+        fn should_render_large_negative_duration() {
+            assert_eq!(
+                hours_mins(-(Duration::hours(205) + Duration::minutes(36))),
+                "-205  hours, 36    minutes"
+            );
+        }
+
+        #[test]
+        fn should_render_exactly_24_hours() {
+            assert_eq!(
+                hours_mins(Duration::hours(24)),
+                "24    hours, 0     minutes"
+            );
+        }
+
+        #[test]
+        fn should_render_negative_minutes_only() {
+            assert_eq!(
+                hours_mins(Duration::minutes(-42)),
+                "-0    hours, 42    minutes"
+            );
+        }
+
+        #[test]
+        fn should_keep_minutes_within_an_hour() {
+            for minutes in -1500..1500 {
+                let rendered = hours_mins(Duration::minutes(minutes));
+                let minute_part: i64 = rendered
+                    .split(", ")
+                    .nth(1)
+                    .and_then(|part| part.split_whitespace().next())
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                assert!((0..60).contains(&minute_part), "{}", rendered);
+            }
+        }
+    }
+
+    mod parse_hour_minute {
+        use super::*;
+        use time::macros::time;
+
+        #[test]
+        fn should_parse_hour_minute() {
+            assert_eq!(parse_hour_minute("09:05").unwrap(), time!(09:05));
+            assert!(parse_hour_minute("9 o'clock").is_err());
+        }
+
+        #[test]
+        fn should_parse_date() {
+            assert_eq!(
+                parse_date("2022-01-05").unwrap(),
+                time::macros::date!(2022 - 01 - 05)
+            );
+            assert!(parse_date("2022/01/05").is_err());
+        }
+    }
+
+    mod now_at {
+        use super::*;
+        use time::macros::{datetime, offset};
+
+        #[test]
+        fn should_flatten_instant_at_offset() {
+            let instant = datetime!(2022 - 01 - 05 23:30:00 UTC);
+            assert_eq!(
+                at_offset(instant, offset!(+02:00)),
+                datetime!(2022 - 01 - 06 01:30:00)
+            );
+            assert_eq!(
+                at_offset(instant, offset!(-05:00)),
+                datetime!(2022 - 01 - 05 18:30:00)
+            );
+        }
+
+        #[test]
+        fn should_parse_utc_offset() {
+            assert_eq!(parse_utc_offset("+02:00").unwrap(), offset!(+02:00));
+            assert_eq!(parse_utc_offset("-09:30").unwrap(), offset!(-09:30));
+            assert!(parse_utc_offset("02:00").is_err());
+        }
+    }
+
+    mod compact_hours_mins {
+        use super::*;
+
+        #[test]
+        fn should_be_terse() {
+            assert_eq!(compact_hours_mins(Duration::minutes(316)), "5h 16m");
+            assert_eq!(compact_hours_mins(Duration::minutes(-316)), "-5h 16m");
+            assert_eq!(compact_hours_mins(Duration::hours(8)), "8h 0m");
+            assert_eq!(compact_hours_mins(Duration::minutes(-16)), "-0h 16m");
+        }
+    }
+
+    mod hours_mins_secs {
+        use super::*;
+
+        #[test]
+        fn should_render_seconds_remainder() {
+            assert_eq!(
+                hours_mins_secs(Duration::seconds(8 * 3600 + 32)),
+                "8     hours, 0     minutes, 32    seconds"
+            );
+        }
+
+        #[test]
+        fn should_render_zero_seconds_without_remainder() {
+            assert_eq!(
+                hours_mins_secs(Duration::minutes(316)),
+                "5     hours, 16    minutes, 0     seconds"
+            );
+        }
+
+        #[test]
+        fn should_sign_first_nonzero_unit() {
+            assert_eq!(
+                hours_mins_secs(Duration::seconds(-75)),
+                "0     hours, -1    minutes, 15    seconds"
+            );
+            assert_eq!(
+                hours_mins_secs(Duration::seconds(-32)),
+                "0     hours, 0     minutes, -32   seconds"
+            );
+        }
+    }
+
+    mod hours_mins_styled {
+        use super::*;
+
+        const NEGATIVE: Duration = Duration::minutes(-(5 * 60 + 16));
+
+        #[test]
+        fn should_lead_with_minus() {
+            assert_eq!(
+                hours_mins_styled(NEGATIVE, NegativeStyle::Minus),
+                "-5    hours, 16    minutes"
+            );
+            assert_eq!(
+                hours_mins_styled(-NEGATIVE, NegativeStyle::Minus),
+                "5     hours, 16    minutes"
+            );
+        }
+
+        #[test]
+        fn should_wrap_in_parentheses_and_stay_aligned() {
+            let negative = hours_mins_styled(NEGATIVE, NegativeStyle::Parentheses);
+            let positive = hours_mins_styled(-NEGATIVE, NegativeStyle::Parentheses);
+            assert_eq!(negative, "(5     hours, 16    minutes)");
+            assert_eq!(positive, " 5     hours, 16    minutes ");
+            assert_eq!(negative.find("hours"), positive.find("hours"));
+            assert_eq!(
+                hours_mins_styled(Duration::minutes(-42), NegativeStyle::Parentheses),
+                "(0     hours, 42    minutes)"
+            );
+        }
+
+        #[test]
+        fn should_parse_style() {
+            assert_eq!(
+                "paren".parse::<NegativeStyle>().unwrap(),
+                NegativeStyle::Parentheses
+            );
+            assert!("brackets".parse::<NegativeStyle>().is_err());
+        }
+    }
+
+    mod hours_mins_grouped {
+        use super::*;
+
+        #[test]
+        fn should_group_thousands() {
+            assert_eq!(
+                hours_mins_grouped(Duration::hours(1205), ','),
+                "1,205 hours, 0     minutes"
+            );
+            assert_eq!(
+                hours_mins_grouped(Duration::hours(1_234_567) + Duration::minutes(1), '.'),
+                "1.234.567 hours, 1     minutes"
+            );
+        }
+
+        #[test]
+        fn should_group_negative_thousands() {
+            assert_eq!(
+                hours_mins_grouped(-(Duration::hours(1205) + Duration::minutes(5)), ','),
+                "-1,205 hours, 5     minutes"
+            );
+        }
+
+        #[test]
+        fn should_match_hours_mins_below_a_thousand() {
+            for minutes in [-6000, -42, -1, 0, 1, 59, 61, 6000] {
+                let duration = Duration::minutes(minutes);
+                assert_eq!(hours_mins_grouped(duration, ','), hours_mins(duration));
+            }
+        }
+    }
+
+    mod decimal_hours {
+        use super::*;
+
+        #[test]
+        fn should_render_hundredths_of_hours() {
+            assert_eq!(decimal_hours(Duration::minutes(8 * 60 + 30)), "8.50");
+            assert_eq!(decimal_hours(Duration::ZERO), "0.00");
+            assert_eq!(decimal_hours(Duration::minutes(-20)), "-0.33");
+        }
+
+        #[test]
+        fn should_use_the_given_separator() {
+            assert_eq!(decimal_hours_with(Duration::minutes(520), ','), "8,67");
+            assert_eq!(decimal_hours_with(Duration::minutes(520), '.'), "8.67");
+        }
+    }
+
+    mod account_owner {
+        use super::*;
+
+        #[test]
+        fn should_take_the_part_after_the_last_colon() {
+            assert_eq!(account_owner("e:fc:fred"), "fred");
+            assert_eq!(account_owner("fred"), "fred");
+            assert_eq!(account_owner("e:fc:"), "");
+        }
+    }
+
+    mod top_level_account {
+        use super::*;
+
+        #[test]
+        fn should_take_the_part_before_the_first_colon() {
+            assert_eq!(top_level_account("acme:dev:meeting"), "acme");
+            assert_eq!(top_level_account("acme"), "acme");
+            assert_eq!(top_level_account(""), "");
+        }
+    }
+
+    mod rollup_by_regex {
+        use super::*;
+        use std::io::Cursor;
+        use time::macros::datetime;
+
+        fn summary() -> Summary {
+            let log = "i 2022/01/03 09:00:00 acme:dev\n\
+                       o 2022/01/03 10:00:00\n\
+                       i 2022/01/03 10:00:00 acme:PROJ-12 review\n\
+                       o 2022/01/03 10:30:00\n\
+                       i 2022/01/03 10:30:00 initech:PROJ-7\n\
+                       o 2022/01/03 12:00:00\n\
+                       i 2022/01/03 12:00:00 lunch\n\
+                       o 2022/01/03 12:15:00\n";
+            let now = datetime!(2022-01-03 13:00:00);
+            summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap()
+        }
+
+        #[test]
+        fn should_group_by_named_capture() {
+            let pattern = Regex::new("^(?P<client>[^:]+):").unwrap();
+            let groups = rollup_by_regex(&summary(), &pattern);
+            assert_eq!(
+                groups.into_iter().collect::<Vec<_>>(),
+                vec![
+                    (UNMATCHED.to_owned(), Duration::minutes(15)),
+                    ("acme".to_owned(), Duration::minutes(90)),
+                    ("initech".to_owned(), Duration::minutes(90)),
+                ]
+            );
+        }
+
+        #[test]
+        fn should_group_by_whole_match_without_groups() {
+            let pattern = Regex::new("PROJ-[0-9]+").unwrap();
+            let groups = rollup_by_regex(&summary(), &pattern);
+            assert_eq!(groups["PROJ-12"], Duration::minutes(30));
+            assert_eq!(groups["PROJ-7"], Duration::minutes(90));
+            assert_eq!(groups[UNMATCHED], Duration::minutes(75));
+        }
+    }
+
+    mod relative_date_label {
+        use super::*;
+        use time::macros::date;
+
+        const TODAY: Date = date!(2022 - 01 - 05);
+
+        #[test]
+        fn should_label_today_and_yesterday() {
+            assert_eq!(relative_date_label(TODAY, TODAY), "Today");
+            assert_eq!(
+                relative_date_label(date!(2022 - 01 - 04), TODAY),
+                "Yesterday"
+            );
+        }
+
+        #[test]
+        fn should_use_weekday_within_last_week() {
+            assert_eq!(relative_date_label(date!(2022 - 01 - 02), TODAY), "Sunday");
+            assert_eq!(
+                relative_date_label(date!(2021 - 12 - 30), TODAY),
+                "Thursday"
+            );
+        }
+
+        #[test]
+        fn should_fall_back_to_iso_date() {
+            assert_eq!(
+                relative_date_label(date!(2021 - 12 - 05), TODAY),
+                "2021-12-05"
+            );
+            assert_eq!(
+                relative_date_label(date!(2021 - 12 - 29), TODAY),
+                "2021-12-29"
+            );
+            assert_eq!(
+                relative_date_label(date!(2022 - 01 - 06), TODAY),
+                "2022-01-06"
+            );
+        }
+    }
+
+    mod end_of_yesterday {
+        use super::*;
+        use time::macros::datetime;
+
+        #[test]
+        fn should_be_just_before_midnight() {
+            let result = end_of_yesterday(&datetime!(2022 - 01 - 05 14:00:00));
+            assert_eq!(result.date(), time::macros::date!(2022 - 01 - 04));
+            assert_eq!(
+                result + Duration::nanoseconds(1),
+                datetime!(2022 - 01 - 05 00:00:00)
+            );
+        }
+    }
+
+    mod intervals {
+        use super::*;
+        use std::io::Cursor;
+        use time::macros::datetime;
+
+        #[test]
+        fn should_yield_closed_and_open_intervals() {
+            let content = r"i 2022/01/01 09:00:00 fred:flintstone
+o 2022/01/01 12:00:00
+i 2022/01/01 13:00:00";
+            let now = datetime!(2022 - 01 - 01 14:00:00);
+            let result: Vec<Interval> = Intervals::new(Cursor::new(content), now)
+                .collect::<anyhow::Result<_>>()
+                .unwrap();
+            assert_eq!(
+                result,
+                vec![
+                    Interval {
+                        start: datetime!(2022 - 01 - 01 09:00:00),
+                        end: datetime!(2022 - 01 - 01 12:00:00),
+                        account: "fred:flintstone".to_owned(),
+                        open: false,
+                    },
+                    Interval {
+                        start: datetime!(2022 - 01 - 01 13:00:00),
+                        end: now,
+                        account: String::new(),
+                        open: true,
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn should_warn_about_future_timestamp_unless_strict() {
+            let content = r"i 2022/01/01 09:00:00 fred:flintstone
+o 2022/01/01 12:00:00
+i 2023/01/01 13:00:00 fred:flintstone
+o 2023/01/01 14:00:00";
+            let now = datetime!(2022 - 01 - 02 14:00:00);
+            let mut intervals = Intervals::new(Cursor::new(content), now);
+            assert_eq!(intervals.by_ref().count(), 2);
+            let warnings: Vec<String> =
+                intervals.warnings().iter().map(|w| w.to_string()).collect();
+            assert_eq!(
+                warnings,
+                vec![
+                    "line 3, column 3: timestamp 2023/01/01 13:00:00 is after now",
+                    "line 4, column 3: timestamp 2023/01/01 14:00:00 is after now",
+                ]
+            );
+            let error = Intervals::new(Cursor::new(content), now)
+                .strict(true)
+                .find_map(Result::err)
+                .unwrap();
+            assert_eq!(
+                error.downcast_ref::<ParseError>().map(|e| (e.line, e.kind)),
+                Some((3, ParseErrorKind::Future))
+            );
+        }
+
+        #[test]
+        fn assume_clockout_should_close_dangling_session_of_past_day() {
+            let content = r"i 2022/01/01 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 03 14:00:00);
+            let assume = Some(time::macros::time!(17:00));
+            let result: Vec<Interval> = Intervals::new(Cursor::new(content), now)
+                .assume_clockout(assume)
+                .collect::<anyhow::Result<_>>()
+                .unwrap();
+            assert_eq!(
+                result,
+                vec![Interval {
+                    start: datetime!(2022 - 01 - 01 09:00:00),
+                    end: datetime!(2022 - 01 - 01 17:00:00),
+                    account: "fred:flintstone".to_owned(),
+                    open: false,
+                }]
+            );
+            let late = r"i 2022/01/01 19:00:00";
+            let result = Intervals::new(Cursor::new(late), now)
+                .assume_clockout(assume)
+                .next()
+                .unwrap()
+                .unwrap();
+            assert_eq!(result.duration(), Duration::ZERO);
+        }
+
+        #[test]
+        fn assume_clockout_should_count_todays_session_to_now() {
+            let content = r"i 2022/01/03 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 03 18:00:00);
+            let result = Intervals::new(Cursor::new(content), now)
+                .assume_clockout(Some(time::macros::time!(17:00)))
+                .next()
+                .unwrap()
+                .unwrap();
+            assert_eq!(result.end, now);
+            assert!(result.open);
+        }
+
+        #[test]
+        fn should_point_at_malformed_timestamp() {
+            let content = r"i 2022/01/01 09:00:00
+o 2022/01/01 12:00:00
+i 2022/01/3x 13:00:00";
+            let now = datetime!(2022 - 01 - 01 14:00:00);
+            let error = Intervals::new(Cursor::new(content), now)
+                .find_map(Result::err)
+                .unwrap();
+            let error = error.downcast::<ParseError>().unwrap();
+            assert_eq!(error.line, 3);
+            assert_eq!(error.column, 3);
+            assert_eq!(error.kind, ParseErrorKind::Timestamp);
+            assert!(error.message.contains("2022/01/3x 13:00:00"));
+        }
+
+        #[test]
+        fn lenient_should_skip_and_collect_unusable_lines() {
+            let content = r"o 2022/01/01 08:00:00
+i 2022/01/01 09:00:00
+x 2022/01/01 10:00:00
+o 2022/01/01 12:00:00
+i 2022/01/01 12:30:00
+o 2022/01/01 12:1z:00
+o 2022/01/01 13:00:00";
+            let now = datetime!(2022 - 01 - 01 14:00:00);
+            let mut intervals = Intervals::new(Cursor::new(content), now).lenient(true);
+            let durations: Vec<Duration> = intervals
+                .by_ref()
+                .map(|interval| interval.unwrap().duration())
+                .collect();
+            assert_eq!(durations, vec![Duration::hours(3), Duration::minutes(30)]);
+            let skipped: Vec<(usize, usize, ParseErrorKind)> = intervals
+                .into_skipped()
+                .into_iter()
+                .map(|error| (error.line, error.column, error.kind))
+                .collect();
+            assert_eq!(
+                skipped,
+                vec![
+                    (1, 1, ParseErrorKind::UnexpectedClockOut),
+                    (3, 1, ParseErrorKind::ClockType),
+                    (6, 3, ParseErrorKind::Timestamp),
+                ]
+            );
+        }
+
+        #[test]
+        fn should_stop_after_an_error() {
+            let content = r"o 2022/01/01 12:00:00
+i 2022/01/01 13:00:00";
+            let now = datetime!(2022 - 01 - 01 14:00:00);
+            let mut intervals = Intervals::new(Cursor::new(content), now);
+            assert!(intervals.next().unwrap().is_err());
+            assert!(intervals.next().is_none());
+        }
+    }
+
+    mod summary {
+        use super::*;
+        use time::macros::datetime;
+
+        struct SummaryNewTestCase {
+            worked_today: Duration,
+            first_punchin_today: PrimitiveDateTime,
+            last_punchin: PrimitiveDateTime,
+            last_punchout: PrimitiveDateTime,
+            total_worked: Duration,
+            num_days_worked: u32,
+            now: PrimitiveDateTime,
+            clocked_in: bool,
+            expected_overtime: Duration,
+            expected_avg_worked: Duration,
+            expected_still_to_work: Duration,
+            expected_still_to_work_8: Duration,
+            expected_time_to_leave: Option<PrimitiveDateTime>,
+            expected_time_to_leave_8: Option<PrimitiveDateTime>,
+        }
+
+        fn aaa_summary_new(tc: &SummaryNewTestCase) {
+            let tally = Tally {
+                worked_today: tc.worked_today,
+                first_punchin_today: Some(tc.first_punchin_today),
+                last_punchin: Some(tc.last_punchin),
+                last_punchout: Some(tc.last_punchout),
+                total_worked: tc.total_worked,
+                num_days_worked: tc.num_days_worked,
+                clocked_in: tc.clocked_in,
+                ..Tally::default()
+            };
+            let result = Summary::new(tally, &tc.now, &Options::default());
+            assert_eq!(result.num_days_worked, tc.num_days_worked);
+            assert_eq!(result.first_punchin_today, Some(tc.first_punchin_today));
+            assert_eq!(result.total_worked, tc.total_worked);
+            assert_eq!(result.avg_worked, Some(tc.expected_avg_worked));
+            assert_eq!(result.overtime, tc.expected_overtime);
+            assert_eq!(result.still_to_work, tc.expected_still_to_work);
+            assert_eq!(result.still_to_work_8, tc.expected_still_to_work_8);
+            assert_eq!(result.time_to_leave_8, tc.expected_time_to_leave_8);
+            assert_eq!(result.time_to_leave, tc.expected_time_to_leave);
+        }
+
+        #[test]
+        fn summary_new_today_only() {
+            let tc = SummaryNewTestCase {
+                now: datetime!(2022 - 04 - 22 09:33:33),
+                worked_today: Duration::hours(3_i64),
+                first_punchin_today: datetime!(2022 - 04 - 22 06:33:33),
+                last_punchin: datetime!(2022 - 04 - 22 12:33:33),
+                last_punchout: datetime!(2022 - 04 - 22 12:00:33),
+                total_worked: Duration::hours(3_i64),
+                num_days_worked: 1u32,
+                clocked_in: true,
+                expected_overtime: Duration::ZERO,
+                expected_avg_worked: Duration::hours(3_i64),
+                expected_still_to_work: Duration::hours(5_i64),
+                expected_still_to_work_8: Duration::hours(5_i64),
+                expected_time_to_leave: Some(datetime!(2022 - 04 - 22 14:33:33)),
+                expected_time_to_leave_8: Some(datetime!(2022 - 04 - 22 14:33:33)),
+            };
+            aaa_summary_new(&tc);
+        }
+
+        #[test]
+        fn summary_new_2_days_positive_overtime() {
+            let tc = SummaryNewTestCase {
+                now: datetime!(2022 - 04 - 22 09:33:33),
+                worked_today: Duration::hours(3_i64),
+                first_punchin_today: datetime!(2022 - 04 - 22 06:33:33),
+                last_punchin: datetime!(2022 - 04 - 22 12:33:33),
+                last_punchout: datetime!(2022 - 04 - 22 12:00:33),
+                total_worked: Duration::hours(12_i64),
+                num_days_worked: 2u32,
+                clocked_in: true,
+                expected_overtime: Duration::hours(1_i64),
+                expected_avg_worked: Duration::hours(6_i64),
+                expected_still_to_work: Duration::hours(4_i64),
+                expected_still_to_work_8: Duration::hours(5_i64),
+                expected_time_to_leave: Some(datetime!(2022 - 04 - 22 13:33:33)),
+                expected_time_to_leave_8: Some(datetime!(2022 - 04 - 22 14:33:33)),
+            };
+            aaa_summary_new(&tc);
+        }
+
+        #[test]
+        fn summary_new_2_days_negative_overtime() {
+            let tc = SummaryNewTestCase {
+                now: datetime!(2022 - 04 - 22 09:33:33),
+                worked_today: Duration::hours(3_i64),
+                first_punchin_today: datetime!(2022 - 04 - 22 06:33:33),
+                last_punchin: datetime!(2022 - 04 - 22 12:33:33),
+                last_punchout: datetime!(2022 - 04 - 22 12:00:33),
+                total_worked: Duration::hours(8_i64),
+                num_days_worked: 2u32,
+                clocked_in: true,
+                expected_overtime: Duration::hours(-3_i64),
+                expected_avg_worked: Duration::hours(4_i64),
+                expected_still_to_work: Duration::hours(8_i64),
+                expected_still_to_work_8: Duration::hours(5_i64),
+                expected_time_to_leave: Some(datetime!(2022 - 04 - 22 17:33:33)),
+                expected_time_to_leave_8: Some(datetime!(2022 - 04 - 22 14:33:33)),
+            };
+            aaa_summary_new(&tc);
+        }
+
+        #[test]
+        fn summary_new_last_state_is_clocked_out() {
+            let tc = SummaryNewTestCase {
+                now: datetime!(2022 - 04 - 22 09:33:33),
+                worked_today: Duration::hours(3_i64),
+                first_punchin_today: datetime!(2022 - 04 - 22 06:33:33),
+                last_punchin: datetime!(2022 - 04 - 22 12:33:33),
+                last_punchout: datetime!(2022 - 04 - 22 12:00:33),
+                total_worked: Duration::hours(8_i64),
+                num_days_worked: 2u32,
+                clocked_in: false,
+                expected_overtime: Duration::hours(-3_i64),
+                expected_avg_worked: Duration::hours(4_i64),
+                expected_still_to_work: Duration::hours(8_i64),
+                expected_still_to_work_8: Duration::hours(5_i64),
+                expected_time_to_leave: None,
+                expected_time_to_leave_8: None,
+            };
+            aaa_summary_new(&tc);
+        }
+    }
+
+    mod daily_map {
+        use super::*;
+        use std::io::Cursor;
+        use time::macros::date;
+
+        #[test]
+        fn should_total_per_start_date() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 12:00:00
+i 2022/01/03 13:00:00 acme
+o 2022/01/03 17:30:00
+i 2022/01/04 22:00:00 acme
+o 2022/01/05 02:00:00
+i 2022/01/05 09:00:00 acme
+o 2022/01/05 10:15:00
+i 2022/01/05 11:00:00 acme";
+            let result = daily_map(Cursor::new(content)).unwrap();
+            let expected: BTreeMap<Date, Duration> = [
+                (date!(2022 - 01 - 03), Duration::minutes(7 * 60 + 30)),
+                (date!(2022 - 01 - 04), Duration::hours(4)),
+                (date!(2022 - 01 - 05), Duration::minutes(75)),
+            ]
+            .into_iter()
+            .collect();
+            assert_eq!(result, expected);
+        }
+    }
+
+    mod summarize_file {
+        use super::*;
+        use std::{fs, process};
+        use time::macros::datetime;
+
+        #[test]
+        fn should_reject_binary_file() {
+            let path = env::temp_dir().join(format!("tlsum-binary-{}", process::id()));
+            fs::write(&path, b"i 2022/01/01 09:00:00\n\xff\xfe\x00\x81\n").unwrap();
+            let now = datetime!(2022 - 01 - 01 12:00:00);
+            let result = summarize_file(&path, &now, &Options::default());
+            fs::remove_file(&path).unwrap();
+            let message = result.err().unwrap().to_string();
+            assert!(message.contains("does not look like a text timelog"));
+            assert!(message.contains(&*path.to_string_lossy()));
+        }
+
+        #[test]
+        fn progress_reports_bytes_consumed() {
+            let content = "i 2022/01/03 09:00:00 acme\no 2022/01/03 17:00:00\n".repeat(10);
+            let mut reported = Vec::new();
+            let reader = Progress::new(
+                io::BufReader::with_capacity(64, content.as_bytes()),
+                |consumed| reported.push(consumed),
+            );
+            let now = datetime!(2022 - 01 - 04 12:00:00);
+            let result = summarize_reader(reader, &now, &Options::default()).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(80));
+            assert!(reported.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(reported.last(), Some(&(content.len() as u64)));
+            assert!(reported.len() >= content.len() / 64);
+        }
+
+        #[test]
+        fn summarize_file_with_progress_ends_at_file_size() {
+            let path = env::temp_dir().join(format!("tlsum-progress-{}", process::id()));
+            fs::write(&path, "i 2022/01/03 09:00:00 acme\no 2022/01/03 17:00:00\n").unwrap();
+            let now = datetime!(2022 - 01 - 04 12:00:00);
+            let mut last = None;
+            let result =
+                summarize_file_with_progress(&path, &now, &Options::default(), |read, size| {
+                    last = Some((read, size));
+                });
+            fs::remove_file(&path).unwrap();
+            assert_eq!(result.unwrap().total_worked, Duration::hours(8));
+            assert_eq!(last, Some((49, 49)));
+        }
+    }
+
+    mod summarize_files {
+        use super::*;
+        use std::{fs, process};
+        use time::macros::datetime;
+
+        fn monthly_dir(name: &str) -> PathBuf {
+            let dir = env::temp_dir().join(format!("tlsum-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir(&dir).unwrap();
+            fs::write(
+                dir.join("timelog-2022-05.txt"),
+                "o 2022/05/01 01:00:00\ni 2022/05/02 09:00:00 acme\no 2022/05/02 17:00:00",
+            )
+            .unwrap();
+            fs::write(
+                dir.join("timelog-2022-04.txt"),
+                "i 2022/04/29 09:00:00 acme\no 2022/04/29 17:00:00\n\
+                 i 2022/04/30 23:00:00 acme\n",
+            )
+            .unwrap();
+            fs::write(dir.join("notes.txt"), "not a timelog").unwrap();
+            dir
+        }
+
+        #[test]
+        fn should_summarize_monthly_files_in_order() {
+            let dir = monthly_dir("monthly");
+            let files = timelog_files(&dir, "timelog-*").unwrap();
+            assert_eq!(
+                files,
+                vec![
+                    dir.join("timelog-2022-04.txt"),
+                    dir.join("timelog-2022-05.txt")
+                ]
+            );
+            let now = datetime!(2022 - 05 - 03 12:00:00);
+            let result = summarize_files(&files, &now, &Options::default()).unwrap();
+            fs::remove_dir_all(&dir).unwrap();
+            assert_eq!(result.num_days_worked, 3);
+            assert_eq!(result.total_worked, Duration::hours(18));
+            assert!(result.warnings.is_empty());
+        }
+
+        #[test]
+        fn should_skip_invalid_file_unless_strict() {
+            let dir = monthly_dir("invalid");
+            fs::write(dir.join("timelog-2022-06.txt"), b"\xff\xfe\x00\x81").unwrap();
+            let files = timelog_files(&dir, "timelog-*").unwrap();
+            let now = datetime!(2022 - 06 - 03 12:00:00);
+            let lenient = summarize_files(&files, &now, &Options::default());
+            let strict = Options {
+                strict: true,
+                ..Options::default()
+            };
+            let strict = summarize_files(&files, &now, &strict);
+            fs::remove_dir_all(&dir).unwrap();
+            let result = lenient.unwrap();
+            assert_eq!(result.total_worked, Duration::hours(18));
+            assert_eq!(result.warnings.len(), 1);
+            assert!(result.warnings[0].message.contains("timelog-2022-06.txt"));
+            assert!(strict.is_err());
+        }
+
+        #[test]
+        fn should_skip_file_with_malformed_line_unless_strict() {
+            let dir = monthly_dir("corrupt");
+            fs::write(
+                dir.join("timelog-2022-06.txt"),
+                "i 2022/06/01 09:00:00 acme\no 2022/06/01 1x:00:00\n",
+            )
+            .unwrap();
+            let files = timelog_files(&dir, "timelog-*").unwrap();
+            let now = datetime!(2022 - 06 - 03 12:00:00);
+            let lenient = summarize_files(&files, &now, &Options::default());
+            let strict = Options {
+                strict: true,
+                ..Options::default()
+            };
+            let strict = summarize_files(&files, &now, &strict);
+            fs::remove_dir_all(&dir).unwrap();
+            let result = lenient.unwrap();
+            assert_eq!(result.total_worked, Duration::hours(18));
+            assert_eq!(result.warnings.len(), 1);
+            assert!(result.warnings[0].message.contains("timelog-2022-06.txt"));
+            assert!(result.warnings[0].message.contains("line 2"));
+            assert!(strict.is_err());
+        }
+    }
+
+    mod summarize_lines {
+        use super::summarize_lines as sut;
+        use super::*;
+        use std::io::{BufReader, Cursor};
+        use time::macros::datetime;
+
+        fn create_reader(s: &'static str) -> Box<dyn BufRead> {
+            let buff = Cursor::new(s);
+            let reader = BufReader::new(buff);
+            Box::new(reader)
+        }
+
+        #[test]
+        fn account_for_still_clocked_in() {
+            let content = "i 2022/01/01 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 01 12:00:00);
+            let reader = create_reader(content);
+            let result = sut(reader, &now, &Options::default()).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(3i64));
+            assert_eq!(result.worked_today, Duration::hours(3i64));
+        }
+
+        #[test]
+        fn account_for_not_clocked_in() {
+            let content = r"i 2022/01/01 09:00:00 fred:flintstone
+o 2022/01/01 11:00:00";
+            let now = datetime!(2022 - 01 - 01 12:00:00);
+            let reader = create_reader(content);
+            let result = sut(reader, &now, &Options::default()).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(2i64));
+            assert_eq!(result.worked_today, Duration::hours(2i64));
+            assert_eq!(result.time_to_leave, None);
+            assert_eq!(result.time_to_leave_8, None);
+        }
+
+        #[test]
+        fn exempt_first_day_expects_one_day_less() {
+            let content = r"i 2022/01/01 09:00:00 fred:flintstone
+o 2022/01/01 18:00:00
+i 2022/01/02 09:00:00 fred:flintstone
+o 2022/01/02 12:00:00";
+            let now = datetime!(2022 - 01 - 02 12:00:00);
+            let reader = create_reader(content);
+            let result = sut(reader, &now, &Options::default()).unwrap();
+            assert_eq!(result.num_days_worked, 2);
+            assert_eq!(result.overtime, Duration::hours(1i64));
+        }
+
+        #[test]
+        fn single_past_day_is_overtime_by_default() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.overtime, Duration::hours(8));
+        }
+
+        #[test]
+        fn first_day_overtime_holds_single_day_against_target() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let options = Options {
+                first_day_overtime: true,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.overtime, Duration::ZERO);
+        }
+
+        #[test]
+        fn no_exempt_first_day_expects_all_days() {
+            let content = r"i 2022/01/01 09:00:00 fred:flintstone
+o 2022/01/01 18:00:00
+i 2022/01/02 09:00:00 fred:flintstone
+o 2022/01/02 12:00:00";
+            let now = datetime!(2022 - 01 - 02 12:00:00);
+            let reader = create_reader(content);
+            let options = Options {
+                exempt_first_day: false,
+                ..Options::default()
+            };
+            let result = sut(reader, &now, &options).unwrap();
+            assert_eq!(result.num_days_worked, 2);
+            assert_eq!(result.overtime, Duration::hours(-7i64));
+            assert_eq!(result.still_to_work, Duration::hours(4));
+
+            let clocked_in = r"i 2022/01/01 09:00:00 fred:flintstone
+o 2022/01/01 18:00:00
+i 2022/01/02 09:00:00 fred:flintstone";
+            let result = sut(create_reader(clocked_in), &now, &options).unwrap();
+            assert_eq!(result.overtime, Duration::hours(-7i64));
+            assert_eq!(result.still_to_work, Duration::hours(4));
+            assert_eq!(
+                result.time_to_leave,
+                Some(datetime!(2022 - 01 - 02 16:00:00))
+            );
+        }
+
+        #[test]
+        fn verbose_traces_every_interval() {
+            let content = r"i 2022/01/01 09:00:00 fred:flintstone
+o 2022/01/01 12:00:00
+i 2022/01/01 13:00:00 barney:rubble
+o 2022/01/01 17:00:00
+i 2022/01/02 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 02 12:00:00);
+            let options = Options {
+                verbose: true,
+                ..Options::default()
+            };
+            let mut trace = Vec::new();
+            summarize_traced(create_reader(content), &now, &options, &mut trace).unwrap();
+            let num_intervals = Intervals::new(create_reader(content), now).count();
+            let trace = String::from_utf8(trace).unwrap();
+            assert_eq!(num_intervals, 3);
+            assert_eq!(trace.lines().count(), num_intervals);
+            assert!(trace.lines().next().unwrap().ends_with("fred:flintstone"));
+        }
+
+        #[test]
+        fn lunch_is_added_to_time_to_leave_when_not_taken() {
+            let content = r"i 2022/01/01 08:00:00 fred:flintstone
+o 2022/01/01 12:00:00
+i 2022/01/01 12:10:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 01 13:30:00);
+            let options = Options {
+                lunch: Some(Duration::minutes(30)),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert!(!result.break_taken_today);
+            assert_eq!(result.still_to_work_8, Duration::minutes(160));
+            assert_eq!(
+                result.time_to_leave_8,
+                Some(datetime!(2022 - 01 - 01 16:40:00))
+            );
+        }
+
+        #[test]
+        fn lunch_is_not_added_to_time_to_leave_when_taken() {
+            let content = r"i 2022/01/01 08:00:00 fred:flintstone
+o 2022/01/01 12:00:00
+i 2022/01/01 12:30:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 01 13:30:00);
+            let options = Options {
+                lunch: Some(Duration::minutes(30)),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert!(result.break_taken_today);
+            assert_eq!(result.still_to_work_8, Duration::hours(3));
+            assert_eq!(
+                result.time_to_leave_8,
+                Some(datetime!(2022 - 01 - 01 16:30:00))
+            );
+        }
+
+        #[test]
+        fn exclude_account_drops_matching_intervals() {
+            let content = r"i 2022/01/01 08:00:00 admin:mail
+o 2022/01/01 09:00:00
+i 2022/01/01 09:00:00 acme:dev
+o 2022/01/01 12:00:00
+i 2022/01/01 13:00:00 admin:meeting
+o 2022/01/01 14:00:00";
+            let now = datetime!(2022 - 01 - 01 15:00:00);
+            let options = Options {
+                exclude_accounts: vec!["admin:*".to_owned()],
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(3));
+            assert_eq!(result.worked_today, Duration::hours(3));
+        }
+
+        #[test]
+        fn exclude_account_applies_after_include() {
+            let content = r"i 2022/01/01 08:00:00 acme:admin
+o 2022/01/01 09:00:00
+i 2022/01/01 09:00:00 acme:dev
+o 2022/01/01 12:00:00
+i 2022/01/01 13:00:00 initech:dev
+o 2022/01/01 14:00:00";
+            let now = datetime!(2022 - 01 - 01 15:00:00);
+            let options = Options {
+                accounts: vec!["acme:*".to_owned()],
+                exclude_accounts: vec!["*:admin".to_owned()],
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(3));
+        }
+
+        #[test]
+        fn alias_merges_renamed_accounts() {
+            let content = r"i 2022/01/01 08:00:00 acme:dev
+o 2022/01/01 10:00:00
+i 2022/01/01 10:00:00 acme-corp:dev
+o 2022/01/01 11:00:00
+i 2022/01/01 11:00:00 acmeish
+o 2022/01/01 12:00:00
+i 2022/01/01 13:00:00
+o 2022/01/01 14:00:00";
+            let now = datetime!(2022 - 01 - 01 15:00:00);
+            let options = Options {
+                aliases: vec![("acme".to_owned(), "acme-corp".to_owned())],
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.projects.len(), 3);
+            assert_eq!(result.projects["acme-corp:dev"], Duration::hours(3));
+            assert_eq!(result.projects["acmeish"], Duration::hours(1));
+            assert_eq!(result.projects[UNASSIGNED], Duration::hours(1));
+        }
+
+        #[test]
+        fn strip_prefix_removes_first_matching_prefix() {
+            let content = r"i 2022/01/01 08:00:00 e:fc:fred
+o 2022/01/01 10:00:00
+i 2022/01/01 10:00:00 e:barney
+o 2022/01/01 11:00:00
+i 2022/01/01 11:00:00 fred
+o 2022/01/01 12:00:00
+i 2022/01/01 13:00:00 e:fc:
+o 2022/01/01 14:00:00";
+            let now = datetime!(2022 - 01 - 01 15:00:00);
+            let options = Options {
+                strip_prefixes: vec!["e:fc:".to_owned(), "e:".to_owned()],
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.projects.len(), 3);
+            assert_eq!(result.projects["fred"], Duration::hours(3));
+            assert_eq!(result.projects["barney"], Duration::hours(1));
+            assert_eq!(result.projects["e:fc:"], Duration::hours(1));
+        }
+
+        #[test]
+        fn punctuality_counts_on_time_and_late_days() {
+            let content = r"i 2022/01/04 09:12:00
+o 2022/01/04 17:00:00
+i 2022/01/05 08:00:00
+o 2022/01/05 09:00:00
+i 2022/01/05 09:30:00
+o 2022/01/05 17:00:00
+i 2022/01/06 09:00:00
+o 2022/01/06 17:00:00";
+            let now = datetime!(2022 - 01 - 06 18:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            let punctuality = result.punctuality(time::macros::time!(09:00), Duration::ZERO);
+            assert_eq!(
+                punctuality,
+                Punctuality {
+                    on_time_days: 2,
+                    late_days: 1,
+                    avg_lateness: Some(Duration::minutes(12)),
+                }
+            );
+        }
+
+        #[test]
+        fn dst_aware_counts_elapsed_time_across_spring_forward() {
+            let content = r"i 2022/03/27 01:00:00 fred:flintstone
+o 2022/03/27 04:00:00";
+            let now = datetime!(2022 - 03 - 27 12:00:00);
+            let naive = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(naive.total_worked, Duration::hours(3));
+            let options = Options {
+                time_zone: Some(TimeZone::from_posix_tz("CET-1CEST,M3.5.0,M10.5.0/3").unwrap()),
+                ..Options::default()
+            };
+            let aware = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(aware.total_worked, Duration::hours(2));
+        }
+
+        #[test]
+        fn accumulates_projects_per_day() {
+            let content = r"i 2022/01/03 09:00:00 acme:dev
+o 2022/01/03 11:00:00
+i 2022/01/03 11:00:00 initech
+o 2022/01/03 12:00:00
+i 2022/01/04 09:00:00 acme:dev
+o 2022/01/04 10:00:00";
+            let now = datetime!(2022 - 01 - 04 12:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            let monday = &result.days[&time::macros::date!(2022 - 01 - 03)];
+            assert_eq!(monday.projects["acme:dev"], Duration::hours(2));
+            assert_eq!(monday.projects["initech"], Duration::hours(1));
+            let tuesday = &result.days[&time::macros::date!(2022 - 01 - 04)];
+            assert_eq!(tuesday.projects.len(), 1);
+            assert_eq!(
+                export::matrix(&result.days, '.'),
+                "date,acme,initech\n2022-01-03,2.00,1.00\n2022-01-04,1.00,0\n"
+            );
+        }
+
+        #[test]
+        fn as_of_yesterday_excludes_today() {
+            let content = r"i 2022/01/03 09:00:00 fred:flintstone
+o 2022/01/03 18:00:00
+i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00
+i 2022/01/05 08:00:00 fred:flintstone
+o 2022/01/05 12:00:00
+i 2022/01/05 12:30:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 05 14:00:00);
+            let options = Options {
+                as_of: Some(end_of_yesterday(&now)),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.num_days_worked, 2);
+            assert_eq!(result.total_worked, Duration::hours(17));
+            assert_eq!(result.worked_today, Duration::hours(8));
+            assert_eq!(result.overtime, Duration::hours(1));
+            assert_eq!(result.time_to_leave, None);
+            assert_eq!(
+                result.last_punchout,
+                Some(datetime!(2022 - 01 - 04 17:00:00))
+            );
+        }
+
+        #[test]
+        fn no_entries_today_when_last_entry_is_yesterday() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert!(result.no_entries_today(&now));
+            let yesterday = datetime!(2022 - 01 - 04 18:00:00);
+            assert!(!result.no_entries_today(&yesterday));
+        }
+
+        #[test]
+        fn entries_today_when_clocked_in_since_yesterday() {
+            let content = r"i 2022/01/04 22:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 05 01:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert!(!result.no_entries_today(&now));
+        }
+
+        #[test]
+        fn billable_and_non_billable_add_up_to_total_worked() {
+            let content = r"i 2022/01/04 09:00:00 acme:dev
+o 2022/01/04 11:00:00
+i 2022/01/04 11:00:00 admin:mail
+o 2022/01/04 11:30:00
+i 2022/01/04 11:30:00 initech:dev
+o 2022/01/04 12:30:00
+i 2022/01/04 13:00:00";
+            let now = datetime!(2022 - 01 - 04 14:00:00);
+            let options = Options {
+                billable: vec!["acme:*".to_owned(), "initech:*".to_owned()],
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.billable, Duration::hours(3));
+            assert_eq!(result.non_billable, Duration::minutes(90));
+            assert_eq!(result.total_worked, result.billable + result.non_billable);
+        }
+
+        #[test]
+        fn epoch_and_standard_lines_mix() {
+            let content = r"i 1641301200 fred:flintstone
+o 2022/01/05 17:00:00";
+            let start = parse_epoch("1641301200").unwrap();
+            let end = datetime!(2022 - 01 - 05 17:00:00);
+            let now = datetime!(2022 - 01 - 06 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.last_punchin, Some(start));
+            assert_eq!(result.total_worked, end - start);
+            assert_eq!(result.projects.get("fred:flintstone"), Some(&(end - start)));
+        }
+
+        #[test]
+        fn focus_ratio_today_leaves_out_breaks() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00
+i 2022/01/05 08:00:00 fred:flintstone
+o 2022/01/05 12:00:00
+i 2022/01/05 13:00:00 fred:flintstone
+o 2022/01/05 17:00:00";
+            let now = datetime!(2022 - 01 - 05 18:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            let ratio = result.focus_ratio_today.unwrap();
+            assert!((ratio - 8.0 / 9.0).abs() < 1e-9);
+        }
+
+        #[test]
+        fn focus_ratio_today_runs_up_to_now_when_clocked_in() {
+            let content = r"i 2022/01/05 08:00:00 fred:flintstone
+o 2022/01/05 09:00:00
+i 2022/01/05 10:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 05 12:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.focus_ratio_today, Some(0.75));
+            let yesterday = r"i 2022/01/04 08:00:00 fred:flintstone
+o 2022/01/04 09:00:00";
+            let result = sut(create_reader(yesterday), &now, &Options::default()).unwrap();
+            assert_eq!(result.focus_ratio_today, None);
+        }
+
+        #[test]
+        fn no_open_session_drops_trailing_clock_in() {
+            let content = r"i 2022/01/05 08:00:00 fred:flintstone
+o 2022/01/05 12:00:00
+i 2022/01/05 12:30:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 05 14:00:00);
+            let options = Options {
+                no_open_session: true,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert!(!result.clocked_in);
+            assert_eq!(result.worked_today, Duration::hours(4));
+            assert_eq!(result.total_worked, Duration::hours(4));
+            assert_eq!(
+                result.last_punchin,
+                Some(datetime!(2022 - 01 - 05 08:00:00))
+            );
+            assert_eq!(result.time_to_leave, None);
+            assert_eq!(result.time_to_leave_8, None);
+        }
+
+        #[test]
+        fn round_applies_to_every_interval() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 09:07:30
+i 2022/01/04 10:00:00 fred:flintstone
+o 2022/01/04 10:22:30";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            let options = Options {
+                round: Some(Duration::minutes(15)),
+                round_mode: RoundMode::Bankers,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.total_worked, Duration::minutes(30));
+        }
+
+        #[test]
+        fn semicolon_comments_are_ignored() {
+            let content = r"; ledger style comment
+i 2022/01/04 09:00:00 fred:flintstone ; quarry
+o 2022/01/04 17:00:00 ; home time
+# emacs style comment";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(8));
+            assert_eq!(
+                result.projects.get("fred:flintstone"),
+                Some(&Duration::hours(8))
+            );
+        }
+
+        #[test]
+        fn fractional_seconds_count_towards_total_worked() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 10:00:00.5
+i 2022/01/04 11:00:00 fred:flintstone
+o 2022/01/04 12:00:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(
+                result.total_worked,
+                Duration::hours(2) + Duration::milliseconds(500)
+            );
+        }
+
+        #[test]
+        fn num_sessions_includes_open_session() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 12:00:00
+i 2022/01/04 13:00:00 fred:flintstone
+o 2022/01/04 17:00:00
+i 2022/01/05 08:00:00 fred:flintstone
+o 2022/01/05 10:00:00
+i 2022/01/05 10:30:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 05 12:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.num_sessions, 4);
+            assert_eq!(result.num_days_worked, 2);
+        }
+
+        #[test]
+        fn avg_session_of_equal_sessions_is_session_length() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 10:30:00
+i 2022/01/04 11:00:00 fred:flintstone
+o 2022/01/04 12:30:00
+i 2022/01/05 09:00:00 fred:flintstone
+o 2022/01/05 10:30:00";
+            let now = datetime!(2022 - 01 - 05 12:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.avg_session, Some(Duration::minutes(90)));
+            let result = sut(create_reader(""), &now, &Options::default()).unwrap();
+            assert_eq!(result.avg_session, None);
+        }
+
+        #[test]
+        fn contract_drives_fixed_day_and_target_drives_balance() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00
+i 2022/01/05 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 05 13:00:00);
+            let options = Options {
+                target: Duration::minutes(450),
+                contract: Some(Duration::hours(8)),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.overtime, Duration::minutes(30));
+            assert_eq!(result.still_to_work_8, Duration::hours(4));
+            assert_eq!(result.still_to_work, Duration::minutes(180));
+            assert_eq!(
+                result.time_to_leave_8,
+                Some(datetime!(2022 - 01 - 05 17:00:00))
+            );
+            assert_eq!(
+                result.time_to_leave,
+                Some(datetime!(2022 - 01 - 05 16:00:00))
+            );
+        }
+
+        #[test]
+        fn leave_plan_ends_open_session_at_planned_time() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 18:00:00
+i 2022/01/05 08:30:00 fred:flintstone";
+            let planned = datetime!(2022 - 01 - 05 16:00:00);
+            let options = Options::default();
+            let result = sut(create_reader(content), &planned, &options).unwrap();
+            assert_eq!(
+                result.leave_plan(planned.date(), &options),
+                LeavePlan {
+                    worked_today: Duration::minutes(450),
+                    day_overtime: Duration::minutes(-30),
+                    balance: Duration::minutes(30),
+                }
+            );
+        }
+
+        #[test]
+        fn first_punchin_today_is_earliest_clockin_of_today() {
+            let content = r"i 2022/01/05 09:00:00 fred:flintstone
+o 2022/01/05 09:30:00
+i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00
+i 2022/01/05 07:45:00 fred:flintstone
+o 2022/01/05 08:15:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let options = Options::default();
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(
+                result.first_punchin_today,
+                Some(datetime!(2022 - 01 - 05 07:45:00))
+            );
+        }
+
+        #[test]
+        fn projected_month_extrapolates_average_over_remaining_weekdays() {
+            let content = r"i 2021/12/31 09:00:00 fred:flintstone
+o 2021/12/31 17:00:00
+i 2022/01/10 09:00:00 fred:flintstone
+o 2022/01/10 17:00:00
+i 2022/01/11 09:00:00 fred:flintstone
+o 2022/01/11 17:00:00
+i 2022/01/12 08:00:00 fred:flintstone
+o 2022/01/12 12:00:00";
+            let now = datetime!(2022 - 01 - 12 12:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.avg_worked, Some(Duration::hours(7)));
+            // 20 hours in January and 13 weekdays left after the 12th.
+            assert_eq!(result.projected_month, Some(Duration::hours(20 + 13 * 7)));
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn summary_serializes_with_serde_feature() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            let json: json::SummaryJson =
+                serde_json::from_str(&json::summary(&result, None)).unwrap();
+            assert_eq!(json.total_worked_seconds, 8 * 3600);
         }
 
         #[test]
-        fn should_parse_clock_out_line() {
-            let line = "o 2022/04/22 21:33:33";
-            let (clock_type, date_time) = parse_line(line).unwrap();
-            assert_eq!(ClockType::Out, clock_type);
-            assert_eq!(datetime!(2022 - 04 - 22 21:33:33), date_time);
+        fn overtime_at_recomputes_with_other_target() {
+            let content = r"i 2022/01/03 09:00:00 fred:flintstone
+o 2022/01/03 17:00:00
+i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 16:00:00
+i 2022/01/05 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let options = Options::default();
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.overtime, Duration::hours(-1));
+            assert_eq!(
+                result.overtime_at(Duration::hours(8), &options),
+                result.overtime
+            );
+            assert_eq!(
+                result.overtime_at(Duration::hours(6), &options),
+                Duration::hours(3)
+            );
         }
-    }
 
-    mod summary {
-        use super::*;
-        use time::macros::datetime;
+        #[test]
+        fn last_worked_is_latest_date_per_account() {
+            let content = r"i 2022/01/03 09:00:00 client:acme
+o 2022/01/03 12:00:00
+i 2022/01/04 09:00:00 client:acme
+o 2022/01/04 10:00:00
+i 2022/01/04 10:00:00 client:initech
+o 2022/01/04 17:00:00
+i 2022/01/05 09:00:00 client:initech
+o 2022/01/05 10:00:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(
+                result.last_worked.get("client:acme"),
+                Some(&time::macros::date!(2022 - 01 - 04))
+            );
+            assert_eq!(
+                result.last_worked.get("client:initech"),
+                Some(&time::macros::date!(2022 - 01 - 05))
+            );
+        }
 
-        struct SummaryNewTestCase {
-            worked_today: Duration,
-            first_punchin_today: PrimitiveDateTime,
-            last_punchin: PrimitiveDateTime,
-            last_punchout: PrimitiveDateTime,
-            total_worked: Duration,
-            num_days_worked: u32,
-            now: PrimitiveDateTime,
-            clocked_in: bool,
-            expected_overtime: Duration,
-            expected_avg_worked: Duration,
-            expected_still_to_work: Duration,
-            expected_still_to_work_8: Duration,
-            expected_time_to_leave: Option<PrimitiveDateTime>,
-            expected_time_to_leave_8: Option<PrimitiveDateTime>,
+        #[test]
+        fn today_reports_figures_of_given_date() {
+            let content = r"i 2022/01/03 09:00:00 fred:flintstone
+o 2022/01/03 17:00:00
+i 2022/01/04 08:30:00 fred:flintstone
+o 2022/01/04 12:00:00
+i 2022/01/04 13:00:00 fred:flintstone
+o 2022/01/04 15:00:00
+i 2022/01/05 09:00:00 fred:flintstone
+o 2022/01/05 10:00:00";
+            let now = datetime!(2022 - 01 - 05 12:00:00);
+            let options = Options {
+                today: Some(time::macros::date!(2022 - 01 - 04)),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.worked_today, Duration::minutes(330));
+            assert_eq!(
+                result.first_punchin_today,
+                Some(datetime!(2022 - 01 - 04 08:30:00))
+            );
         }
 
-        fn aaa_summary_new(tc: &SummaryNewTestCase) {
-            let result = Summary::new(
-                tc.worked_today,
-                Some(tc.first_punchin_today),
-                Some(tc.last_punchin),
-                Some(tc.last_punchout),
-                tc.total_worked,
-                tc.num_days_worked,
-                &tc.now,
-                tc.clocked_in,
+        #[test]
+        fn owner_isolates_one_persons_time() {
+            let content = r"i 2022/01/04 09:00:00 e:fc:fred
+o 2022/01/04 12:00:00
+i 2022/01/04 09:30:00 e:fc:barney
+o 2022/01/04 17:30:00
+i 2022/01/04 13:00:00 e:dev:fred
+o 2022/01/04 14:00:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let options = Options {
+                owner: Some("fred".to_owned()),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(4));
+            assert_eq!(result.num_sessions, 2);
+            assert!(!result.projects.contains_key("e:fc:barney"));
+        }
+
+        #[test]
+        fn hour_histogram_splits_intervals_over_hours() {
+            let content = r"i 2022/01/04 09:30:00 fred:flintstone
+o 2022/01/04 11:15:00
+i 2022/01/04 23:45:00 fred:flintstone
+o 2022/01/05 00:30:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            let minutes: Vec<(usize, i64)> = result
+                .hour_histogram
+                .iter()
+                .enumerate()
+                .filter(|(_, worked)| !worked.is_zero())
+                .map(|(hour, worked)| (hour, worked.whole_minutes()))
+                .collect();
+            assert_eq!(
+                minutes,
+                vec![(0, 30), (9, 30), (10, 60), (11, 15), (23, 15)]
             );
-            assert_eq!(result.num_days_worked, tc.num_days_worked);
-            assert_eq!(result.first_punchin_today, Some(tc.first_punchin_today));
-            assert_eq!(result.total_worked, tc.total_worked);
-            assert_eq!(result.avg_worked, Some(tc.expected_avg_worked));
-            assert_eq!(result.overtime, tc.expected_overtime);
-            assert_eq!(result.still_to_work, tc.expected_still_to_work);
-            assert_eq!(result.still_to_work_8, tc.expected_still_to_work_8);
-            assert_eq!(result.time_to_leave_8, tc.expected_time_to_leave_8);
-            assert_eq!(result.time_to_leave, tc.expected_time_to_leave);
         }
 
         #[test]
-        fn summary_new_today_only() {
-            let tc = SummaryNewTestCase {
-                now: datetime!(2022 - 04 - 22 09:33:33),
-                worked_today: Duration::hours(3_i64),
-                first_punchin_today: datetime!(2022 - 04 - 22 06:33:33),
-                last_punchin: datetime!(2022 - 04 - 22 12:33:33),
-                last_punchout: datetime!(2022 - 04 - 22 12:00:33),
-                total_worked: Duration::hours(3_i64),
-                num_days_worked: 1u32,
-                clocked_in: true,
-                expected_overtime: Duration::ZERO,
-                expected_avg_worked: Duration::hours(3_i64),
-                expected_still_to_work: Duration::hours(5_i64),
-                expected_still_to_work_8: Duration::hours(5_i64),
-                expected_time_to_leave: Some(datetime!(2022 - 04 - 22 14:33:33)),
-                expected_time_to_leave_8: Some(datetime!(2022 - 04 - 22 14:33:33)),
+        fn cap_daily_moves_excess_to_uncounted_overtime() {
+            let content = r"i 2022/01/03 08:00:00 fred:flintstone
+o 2022/01/03 13:00:00
+i 2022/01/03 13:30:00 fred:flintstone
+o 2022/01/03 18:30:00
+i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 16:00:00";
+            let now = datetime!(2022 - 01 - 05 10:00:00);
+            let options = Options {
+                cap_daily: true,
+                ..Options::default()
             };
-            aaa_summary_new(&tc);
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(15));
+            assert_eq!(result.uncounted_overtime, Duration::hours(2));
+            assert_eq!(result.overtime, Duration::hours(7));
+            assert_eq!(result.projects["fred:flintstone"], Duration::hours(15));
         }
 
         #[test]
-        fn summary_new_2_days_positive_overtime() {
-            let tc = SummaryNewTestCase {
-                now: datetime!(2022 - 04 - 22 09:33:33),
-                worked_today: Duration::hours(3_i64),
-                first_punchin_today: datetime!(2022 - 04 - 22 06:33:33),
-                last_punchin: datetime!(2022 - 04 - 22 12:33:33),
-                last_punchout: datetime!(2022 - 04 - 22 12:00:33),
-                total_worked: Duration::hours(12_i64),
-                num_days_worked: 2u32,
-                clocked_in: true,
-                expected_overtime: Duration::hours(1_i64),
-                expected_avg_worked: Duration::hours(6_i64),
-                expected_still_to_work: Duration::hours(4_i64),
-                expected_still_to_work_8: Duration::hours(5_i64),
-                expected_time_to_leave: Some(datetime!(2022 - 04 - 22 13:33:33)),
-                expected_time_to_leave_8: Some(datetime!(2022 - 04 - 22 14:33:33)),
+        fn overtime_through_selects_whether_today_counts() {
+            let content = r"i 2022/01/03 09:00:00 fred:flintstone
+o 2022/01/03 18:00:00
+i 2022/01/04 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 04 12:00:00);
+            let yesterday = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(yesterday.overtime, Duration::hours(1));
+            let options = Options {
+                overtime_through: OvertimeThrough::Today,
+                ..Options::default()
             };
-            aaa_summary_new(&tc);
+            let today = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(today.overtime, Duration::hours(-4));
+            assert_eq!(today.still_to_work, yesterday.still_to_work);
+            assert_eq!(today.still_to_work, Duration::hours(4));
         }
 
         #[test]
-        fn summary_new_2_days_negative_overtime() {
-            let tc = SummaryNewTestCase {
-                now: datetime!(2022 - 04 - 22 09:33:33),
-                worked_today: Duration::hours(3_i64),
-                first_punchin_today: datetime!(2022 - 04 - 22 06:33:33),
-                last_punchin: datetime!(2022 - 04 - 22 12:33:33),
-                last_punchout: datetime!(2022 - 04 - 22 12:00:33),
-                total_worked: Duration::hours(8_i64),
-                num_days_worked: 2u32,
-                clocked_in: true,
-                expected_overtime: Duration::hours(-3_i64),
-                expected_avg_worked: Duration::hours(4_i64),
-                expected_still_to_work: Duration::hours(8_i64),
-                expected_still_to_work_8: Duration::hours(5_i64),
-                expected_time_to_leave: Some(datetime!(2022 - 04 - 22 17:33:33)),
-                expected_time_to_leave_8: Some(datetime!(2022 - 04 - 22 14:33:33)),
+        fn overtime_through_today_expects_today_once_without_exemption() {
+            let content = r"i 2022/01/03 09:00:00 fred:flintstone
+o 2022/01/03 18:00:00
+i 2022/01/04 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 01 - 04 12:00:00);
+            let options = Options {
+                exempt_first_day: false,
+                overtime_through: OvertimeThrough::Today,
+                ..Options::default()
             };
-            aaa_summary_new(&tc);
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.overtime, Duration::hours(-4));
         }
 
         #[test]
-        fn summary_new_last_state_is_clocked_out() {
-            let tc = SummaryNewTestCase {
-                now: datetime!(2022 - 04 - 22 09:33:33),
-                worked_today: Duration::hours(3_i64),
-                first_punchin_today: datetime!(2022 - 04 - 22 06:33:33),
-                last_punchin: datetime!(2022 - 04 - 22 12:33:33),
-                last_punchout: datetime!(2022 - 04 - 22 12:00:33),
-                total_worked: Duration::hours(8_i64),
-                num_days_worked: 2u32,
-                clocked_in: false,
-                expected_overtime: Duration::hours(-3_i64),
-                expected_avg_worked: Duration::hours(4_i64),
-                expected_still_to_work: Duration::hours(8_i64),
-                expected_still_to_work_8: Duration::hours(5_i64),
-                expected_time_to_leave: None,
-                expected_time_to_leave_8: None,
+        fn off_days_worked_counts_weekend_days_and_holidays() {
+            let content = r"i 2022/04/16 09:00:00 fred:flintstone
+o 2022/04/16 12:00:00
+i 2022/04/18 09:00:00 fred:flintstone
+o 2022/04/18 17:00:00
+i 2022/04/19 09:00:00 fred:flintstone
+o 2022/04/19 17:00:00";
+            let now = datetime!(2022 - 04 - 20 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            let holidays = BTreeSet::from([
+                time::macros::date!(2022 - 04 - 17),
+                time::macros::date!(2022 - 04 - 18),
+            ]);
+            assert_eq!(
+                result.off_days_worked(&holidays),
+                OffDaysWorked {
+                    saturdays: 1,
+                    sundays: 0,
+                    holidays: 1,
+                }
+            );
+        }
+
+        #[test]
+        fn vacation_directive_lowers_expected_hours_of_its_date() {
+            let content = r"i 2022/04/21 09:00:00 fred:flintstone
+o 2022/04/21 17:00:00
+# vacation 2022/04/22 4h
+# vacation at the seaside, an ordinary comment
+i 2022/04/22 09:00:00 fred:flintstone
+o 2022/04/22 13:00:00
+i 2022/04/25 09:00:00 fred:flintstone";
+            let now = datetime!(2022 - 04 - 25 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(
+                result.vacations,
+                BTreeMap::from([(time::macros::date!(2022 - 04 - 22), Duration::hours(4))])
+            );
+            // 12 hours worked against 2 days of 8 hours less 4 hours vacation.
+            assert_eq!(result.overtime, Duration::ZERO);
+            assert_eq!(result.still_to_work, Duration::hours(7));
+        }
+
+        #[test]
+        fn streaks_are_broken_by_a_day_without_work() {
+            let content = r"i 2022/01/03 09:00:00 fred:flintstone
+o 2022/01/03 17:00:00
+i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00
+i 2022/01/05 09:00:00 fred:flintstone
+o 2022/01/05 17:00:00
+i 2022/01/06 09:00:00 fred:flintstone
+o 2022/01/06 17:00:00
+i 2022/01/10 09:00:00 fred:flintstone
+o 2022/01/10 17:00:00
+i 2022/01/11 09:00:00 fred:flintstone
+o 2022/01/11 17:00:00";
+            let now = datetime!(2022 - 01 - 12 08:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!((result.current_streak, result.longest_streak), (2, 4));
+            let later = datetime!(2022 - 01 - 13 08:00:00);
+            let result = sut(create_reader(content), &later, &Options::default()).unwrap();
+            assert_eq!((result.current_streak, result.longest_streak), (0, 4));
+        }
+
+        #[test]
+        fn streak_skips_weekends_when_configured() {
+            let content = r"i 2022/01/07 09:00:00 fred:flintstone
+o 2022/01/07 17:00:00
+i 2022/01/10 09:00:00 fred:flintstone
+o 2022/01/10 17:00:00";
+            let now = datetime!(2022 - 01 - 10 18:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!((result.current_streak, result.longest_streak), (1, 1));
+            let options = Options {
+                streak_skips_weekends: true,
+                ..Options::default()
             };
-            aaa_summary_new(&tc);
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!((result.current_streak, result.longest_streak), (2, 2));
         }
-    }
 
-    mod summarize_lines {
-        use super::summarize_lines as sut;
-        use super::*;
-        use std::io::{BufReader, Cursor};
-        use time::macros::datetime;
+        #[test]
+        fn clock_in_with_only_a_note_is_unassigned() {
+            let content = r"i 2022/04/22 09:00:00 # note
+o 2022/04/22 10:00:00
+i 2022/04/22 10:00:00 ; standup
+o 2022/04/22 10:15:00
+i 2022/04/22 10:15:00 acme
+o 2022/04/22 11:00:00";
+            let now = datetime!(2022 - 04 - 22 12:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.projects[UNASSIGNED], Duration::minutes(75));
+            assert_eq!(result.projects["acme"], Duration::minutes(45));
+            assert_eq!(result.projects.len(), 2);
+        }
 
-        fn create_reader(s: &'static str) -> Box<dyn BufRead> {
-            let buff = Cursor::new(s);
-            let reader = BufReader::new(buff);
-            Box::new(reader)
+        #[test]
+        fn merge_adjacent_counts_touching_intervals_as_one_session() {
+            let content = r"i 2022/01/04 09:00:00 acme
+o 2022/01/04 10:00:00
+i 2022/01/04 10:00:00 initech
+o 2022/01/04 12:00:00
+i 2022/01/04 13:00:00 acme
+o 2022/01/04 14:00:00";
+            let now = datetime!(2022 - 01 - 04 15:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.num_sessions, 3);
+            let options = Options {
+                merge_adjacent: true,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.num_sessions, 2);
+            assert_eq!(result.avg_session, Some(Duration::hours(2)));
+            assert_eq!(result.projects["acme"], Duration::hours(2));
+            assert_eq!(result.projects["initech"], Duration::hours(2));
         }
 
         #[test]
-        fn account_for_still_clocked_in() {
-            let content = "i 2022/01/01 09:00:00 fred:flintstone";
-            let now = datetime!(2022 - 01 - 01 12:00:00);
-            let reader = create_reader(content);
-            let result = sut(reader, &now).unwrap();
-            assert_eq!(result.total_worked, Duration::hours(3i64));
-            assert_eq!(result.worked_today, Duration::hours(3i64));
+        fn comment_chars_configure_the_comment_markers() {
+            let content = r"% clocked from home
+i 2022/01/04 09:00:00 acme % standup
+o 2022/01/04 10:00:00";
+            let now = datetime!(2022 - 01 - 04 12:00:00);
+            assert!(sut(create_reader(content), &now, &Options::default()).is_err());
+            let options = Options {
+                comment_chars: vec!['%'],
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.projects["acme"], Duration::hours(1));
         }
 
         #[test]
-        fn account_for_not_clocked_in() {
-            let content = r"i 2022/01/01 09:00:00 fred:flintstone
-o 2022/01/01 11:00:00";
-            let now = datetime!(2022 - 01 - 01 12:00:00);
-            let reader = create_reader(content);
-            let result = sut(reader, &now).unwrap();
-            assert_eq!(result.total_worked, Duration::hours(2i64));
-            assert_eq!(result.worked_today, Duration::hours(2i64));
-            assert_eq!(result.time_to_leave, None);
-            assert_eq!(result.time_to_leave_8, None);
+        fn compact_lines_mix_with_paired_lines() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 12:00:00
+i 2022/01/03 12:30:00 - 2022/01/03 17:00:00 acme
+i 2022/01/04 08:00:00 - 2022/01/04 09:30:00 beta
+i 2022/01/04 10:00:00 acme";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.projects["acme"], Duration::minutes(8 * 60 + 30));
+            assert_eq!(result.projects["beta"], Duration::minutes(90));
+            assert_eq!(result.worked_today, Duration::minutes(150));
+            assert_eq!(result.num_sessions, 4);
+            assert!(result.clocked_in);
+        }
+
+        #[test]
+        fn trust_clockout_account_attributes_unnamed_clock_in() {
+            let content = r"i 2022/01/03 09:00:00
+o 2022/01/03 12:00:00 acme
+i 2022/01/03 12:30:00 beta
+o 2022/01/03 17:00:00 acme";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.projects[UNASSIGNED], Duration::hours(3));
+            let options = Options {
+                trust_clockout_account: true,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.projects["acme"], Duration::hours(3));
+            assert_eq!(result.projects["beta"], Duration::minutes(270));
+            assert!(!result.projects.contains_key(UNASSIGNED));
+        }
+
+        #[test]
+        fn recorded_total_leaves_out_open_session() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 12:00:00
+i 2022/01/03 12:30:00 acme";
+            let now = datetime!(2022 - 01 - 03 14:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.recorded_total, Duration::hours(3));
+            assert_eq!(result.total_worked, Duration::minutes(270));
+            assert!(result.total_worked > result.recorded_total);
+        }
+
+        #[test]
+        fn min_day_leaves_trivial_days_out_of_day_count() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 17:00:00
+i 2022/01/04 09:00:00 mail
+o 2022/01/04 09:10:00
+i 2022/01/05 09:00:00 acme
+o 2022/01/05 16:00:00";
+            let now = datetime!(2022 - 01 - 06 09:00:00);
+            let options = Options {
+                min_day: Some(Duration::minutes(30)),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.num_days_worked, 2);
+            assert_eq!(result.avg_worked, Some(Duration::minutes(450)));
+            assert_eq!(result.total_worked, Duration::minutes(15 * 60 + 10));
+            let days = result.daily_balance(&options);
+            assert_eq!(days.last().unwrap().balance, result.overtime);
+            let all = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(all.num_days_worked, 3);
+        }
+
+        #[test]
+        fn clock_markers_replace_i_and_o() {
+            let content = r"start 2022/01/03 09:00:00 acme
+stop 2022/01/03 12:00:00
+start 2022/01/03 12:30:00 beta
+stop 2022/01/03 13:00:00";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            let options = Options {
+                in_marker: "start".to_owned(),
+                out_marker: "stop".to_owned(),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.projects["acme"], Duration::hours(3));
+            assert_eq!(result.projects["beta"], Duration::minutes(30));
+            assert_eq!(result.num_sessions, 2);
+
+            assert!(sut(create_reader(content), &now, &Options::default()).is_err());
+            let error = sut(create_reader("i 2022/01/03 09:00:00 acme"), &now, &options)
+                .err()
+                .unwrap()
+                .downcast::<ParseError>()
+                .unwrap();
+            assert_eq!(error.kind, ParseErrorKind::ClockType);
+            let error = sut(create_reader("stop 2022/01/03 1x:00:00"), &now, &options)
+                .err()
+                .unwrap()
+                .downcast::<ParseError>()
+                .unwrap();
+            assert_eq!(error.column, 6);
+        }
+
+        #[test]
+        fn break_due_counts_down_the_open_session() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 10:00:00
+i 2022/01/03 10:30:00 acme";
+            let limit = Duration::hours(2);
+            let now = datetime!(2022 - 01 - 03 11:48:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.break_due(&now, limit), Some(Duration::minutes(42)));
+            let now = datetime!(2022 - 01 - 03 13:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.break_due(&now, limit), Some(Duration::ZERO));
+            let closed = sut(
+                create_reader("i 2022/01/03 09:00:00 acme\no 2022/01/03 10:00:00"),
+                &now,
+                &Options::default(),
+            )
+            .unwrap();
+            assert_eq!(closed.break_due(&now, limit), None);
+        }
+
+        #[test]
+        fn still_to_work_week_asks_no_weekend_hours() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 17:00:00
+i 2022/01/04 09:00:00 acme
+o 2022/01/04 17:00:00
+i 2022/01/05 09:00:00 acme
+o 2022/01/05 17:00:00
+i 2022/01/06 09:00:00 acme
+o 2022/01/06 17:00:00
+i 2022/01/07 09:00:00 acme
+o 2022/01/07 13:00:00";
+            let friday = datetime!(2022 - 01 - 07 14:00:00);
+            let result = sut(create_reader(content), &friday, &Options::default()).unwrap();
+            assert_eq!(result.still_to_work_week, Duration::hours(4));
+            let saturday = datetime!(2022 - 01 - 08 10:00:00);
+            let result = sut(create_reader(content), &saturday, &Options::default()).unwrap();
+            assert_eq!(workdays_this_week(saturday.date()), 5);
+            assert_eq!(result.still_to_work_week, Duration::hours(4));
+        }
+
+        #[test]
+        fn total_overtime_including_today_adds_todays_progress() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 17:00:00
+i 2022/01/04 09:00:00 acme
+o 2022/01/04 18:00:00
+i 2022/01/05 09:00:00 acme";
+            let now = datetime!(2022 - 01 - 05 12:00:00);
+            let options = Options::default();
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.overtime, Duration::hours(1));
+            assert_eq!(
+                result.total_overtime_including_today(&options),
+                result.overtime + result.worked_today - options.target
+            );
+            assert_eq!(
+                result.total_overtime_including_today(&options),
+                Duration::hours(-4)
+            );
+            let through_today = Options {
+                overtime_through: OvertimeThrough::Today,
+                ..options
+            };
+            let result = sut(create_reader(content), &now, &through_today).unwrap();
+            assert_eq!(
+                result.total_overtime_including_today(&through_today),
+                result.overtime
+            );
+        }
+
+        #[test]
+        fn overtime_ratio_should_relate_overtime_to_expected_hours() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 17:00:00
+i 2022/01/04 09:00:00 acme
+o 2022/01/04 18:00:00";
+            let now = datetime!(2022 - 01 - 05 12:00:00);
+            let options = Options {
+                exempt_first_day: false,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.overtime_ratio(&options), Some(0.0625));
+            let options = Options::default();
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 17:00:00";
+            let now = datetime!(2022 - 01 - 04 12:00:00);
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.overtime_ratio(&options), None);
+        }
+
+        #[test]
+        fn skip_leading_out_ignores_clock_outs_before_first_clock_in() {
+            let content = r"# the top of this log was cut off
+o 2022/01/03 08:30:00
+o 2022/01/03 08:45:00 acme
+i 2022/01/03 09:00:00 acme
+o 2022/01/03 17:00:00";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            assert!(sut(create_reader(content), &now, &Options::default()).is_err());
+            let options = Options {
+                skip_leading_out: true,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(8));
+            let stray = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 17:00:00
+o 2022/01/03 17:30:00";
+            assert!(sut(create_reader(stray), &now, &options).is_err());
+        }
+
+        #[test]
+        fn punctuality_counts_starts_within_grace_as_on_time() {
+            let content = r"i 2022/01/04 09:05:00
+o 2022/01/04 17:00:00
+i 2022/01/05 09:10:00
+o 2022/01/05 17:00:00
+i 2022/01/06 09:11:00
+o 2022/01/06 17:00:00";
+            let now = datetime!(2022 - 01 - 06 18:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            let punctuality = result.punctuality(time::macros::time!(09:00), Duration::minutes(10));
+            assert_eq!(
+                punctuality,
+                Punctuality {
+                    on_time_days: 2,
+                    late_days: 1,
+                    avg_lateness: Some(Duration::minutes(11)),
+                }
+            );
+        }
+
+        #[test]
+        fn stddev_worked_is_the_spread_of_daily_totals() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 15:00:00
+i 2022/01/04 09:00:00 acme
+o 2022/01/04 17:00:00
+i 2022/01/05 08:00:00 acme
+o 2022/01/05 12:00:00
+i 2022/01/05 13:00:00 acme
+o 2022/01/05 19:00:00";
+            let now = datetime!(2022 - 01 - 06 11:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.avg_worked, Some(Duration::hours(8)));
+            // Deviations of -2, 0 and +2 hours, a variance of 8/3 square hours.
+            let expected = Duration::seconds_f64((8.0_f64 / 3.0).sqrt() * 3600.0);
+            assert_eq!(result.stddev_worked, Some(expected));
+            let empty = sut(create_reader(""), &now, &Options::default()).unwrap();
+            assert_eq!(empty.stddev_worked, None);
+        }
+
+        #[test]
+        fn resume_latest_discards_earlier_dangling_clock_in() {
+            let content = r"i 2022/01/03 08:00:00 acme
+i 2022/01/03 09:00:00 beta
+o 2022/01/03 17:00:00";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            assert!(sut(create_reader(content), &now, &Options::default()).is_err());
+            let options = Options {
+                resume_latest: true,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(result.total_worked, Duration::hours(8));
+            assert_eq!(result.projects["beta"], Duration::hours(8));
+            assert!(!result.projects.contains_key("acme"));
+            let warning = &result.warnings[0];
+            assert_eq!(warning.line, Some(1));
+            assert_eq!(
+                warning.message,
+                "discarded clock in without clock out, resumed at line 2"
+            );
+        }
+
+        #[test]
+        fn parts_of_day_split_intervals_at_the_boundaries() {
+            let content = r"i 2022/01/03 11:30:00 acme
+o 2022/01/03 13:00:00
+i 2022/01/03 17:45:00 acme
+o 2022/01/03 19:00:00";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(
+                result.parts_of_day(),
+                PartsOfDay {
+                    morning: Duration::minutes(30),
+                    afternoon: Duration::minutes(75),
+                    evening: Duration::hours(1),
+                }
+            );
+        }
+
+        #[test]
+        fn schedule_holds_each_day_against_the_target_in_effect() {
+            let content = r"i 2022/05/30 09:00:00 acme
+o 2022/05/30 17:00:00
+i 2022/05/31 09:00:00 acme
+o 2022/05/31 17:00:00
+i 2022/06/01 09:00:00 acme
+o 2022/06/01 17:00:00
+i 2022/06/02 09:00:00 acme";
+            let now = datetime!(2022 - 06 - 02 12:00:00);
+            let flat = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(flat.overtime, Duration::ZERO);
+            let options = Options {
+                schedule: BTreeMap::from([
+                    (time::macros::date!(2022 - 01 - 01), Duration::hours(8)),
+                    (time::macros::date!(2022 - 06 - 01), Duration::hours(6)),
+                ]),
+                ..Options::default()
+            };
+            assert_eq!(
+                options.target_on(time::macros::date!(2022 - 05 - 31)),
+                Duration::hours(8)
+            );
+            assert_eq!(
+                options.target_on(time::macros::date!(2022 - 06 - 01)),
+                Duration::hours(6)
+            );
+            assert_eq!(
+                options.target_on(time::macros::date!(2021 - 12 - 31)),
+                options.target
+            );
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            // 24 hours worked before today against 8 + 8 + 6.
+            assert_eq!(result.overtime, Duration::hours(2));
+            assert_eq!(result.still_to_work, Duration::hours(1));
+            assert_eq!(
+                result.total_overtime_including_today(&options),
+                Duration::hours(-1)
+            );
+            // 8 + 8 + 6 + 6 expected this week, 27 hours worked.
+            assert_eq!(result.still_to_work_week, Duration::hours(1));
+            let capped = Options {
+                cap_daily: true,
+                ..options
+            };
+            let result = sut(create_reader(content), &now, &capped).unwrap();
+            assert_eq!(result.uncounted_overtime, Duration::hours(2));
+        }
+
+        #[test]
+        fn read_schedule_parses_effective_dates_and_hours() {
+            let path = std::env::temp_dir().join(format!("tlsum-schedule-{}", std::process::id()));
+            std::fs::write(&path, "# contract\n2022-01-01,8\n\n2022-06-01, 6.5\n").unwrap();
+            let schedule = read_schedule(&path).unwrap();
+            std::fs::write(&path, "2022-06-01,25\n").unwrap();
+            let out_of_range = read_schedule(&path);
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(
+                schedule,
+                BTreeMap::from([
+                    (time::macros::date!(2022 - 01 - 01), Duration::hours(8)),
+                    (time::macros::date!(2022 - 06 - 01), Duration::minutes(390)),
+                ])
+            );
+            assert!(out_of_range.is_err());
+        }
+
+        #[test]
+        fn leave_plan_balance_counts_today_once() {
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00
+i 2022/01/05 09:00:00 fred:flintstone";
+            let planned = datetime!(2022 - 01 - 05 17:00:00);
+            let today = planned.date();
+            let balanced = LeavePlan {
+                worked_today: Duration::hours(8),
+                day_overtime: Duration::ZERO,
+                balance: Duration::ZERO,
+            };
+            for options in [
+                Options {
+                    exempt_first_day: false,
+                    ..Options::default()
+                },
+                Options {
+                    overtime_through: OvertimeThrough::Today,
+                    ..Options::default()
+                },
+            ] {
+                let result = sut(create_reader(content), &planned, &options).unwrap();
+                assert_eq!(result.leave_plan(today, &options), balanced);
+            }
+            let options = Options {
+                schedule: BTreeMap::from([(today, Duration::hours(6))]),
+                ..Options::default()
+            };
+            let content = r"i 2022/01/04 09:00:00 fred:flintstone
+o 2022/01/04 17:00:00
+# vacation 2022/01/05 1h
+i 2022/01/05 09:00:00 fred:flintstone";
+            let result = sut(create_reader(content), &planned, &options).unwrap();
+            assert_eq!(
+                result.leave_plan(today, &options),
+                LeavePlan {
+                    worked_today: Duration::hours(8),
+                    day_overtime: Duration::hours(3),
+                    balance: Duration::hours(3),
+                }
+            );
+        }
+
+        #[test]
+        fn vacation_is_not_credited_on_the_exempt_first_day() {
+            let content = r"# vacation 2022/04/21 4h
+i 2022/04/21 09:00:00 fred:flintstone
+o 2022/04/21 13:00:00";
+            let now = datetime!(2022 - 04 - 22 10:00:00);
+            let result = sut(create_reader(content), &now, &Options::default()).unwrap();
+            assert_eq!(result.overtime, Duration::hours(4));
+            let options = Options {
+                exempt_first_day: false,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            // 4 hours worked against 8 hours less 4 hours vacation.
+            assert_eq!(result.overtime, Duration::ZERO);
+        }
+
+        #[test]
+        fn vacation_directive_follows_the_comment_chars() {
+            let content = r"% vacation 2022/04/22 4h
+i 2022/04/21 09:00:00 fred:flintstone
+o 2022/04/21 17:00:00
+i 2022/04/22 09:00:00 fred:flintstone
+o 2022/04/22 13:00:00";
+            let now = datetime!(2022 - 04 - 25 10:00:00);
+            let options = Options {
+                comment_chars: vec!['%'],
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(
+                result.vacations,
+                BTreeMap::from([(time::macros::date!(2022 - 04 - 22), Duration::hours(4))])
+            );
+        }
+
+        #[test]
+        fn hour_histogram_holds_the_counted_time() {
+            let content = r"i 2022/01/03 09:00:00 acme
+o 2022/01/03 09:50:00
+i 2022/01/03 10:00:00 acme
+o 2022/01/03 19:00:00";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            let options = Options {
+                round: Some(Duration::HOUR),
+                cap_daily: true,
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            // An hour from 09:00 and the remaining 7 of the capped 8 from 10:00.
+            let histogram = result.hour_histogram.iter().copied().sum::<Duration>();
+            assert_eq!(histogram, result.total_worked);
+            assert_eq!(result.hour_histogram[9], Duration::HOUR);
+            assert_eq!(result.hour_histogram[16], Duration::HOUR);
+            assert_eq!(result.hour_histogram[17], Duration::ZERO);
+        }
+
+        #[test]
+        fn parts_of_day_add_up_to_the_time_worked() {
+            let content = r"i 2022/01/03 11:40:00 acme
+o 2022/01/03 12:10:00
+i 2022/01/03 17:50:00 acme
+o 2022/01/03 18:05:00";
+            let now = datetime!(2022 - 01 - 04 11:00:00);
+            let options = Options {
+                round: Some(Duration::HOUR),
+                ..Options::default()
+            };
+            let result = sut(create_reader(content), &now, &options).unwrap();
+            assert_eq!(
+                result.parts_of_day(),
+                PartsOfDay {
+                    morning: Duration::minutes(20),
+                    afternoon: Duration::minutes(40),
+                    evening: Duration::ZERO,
+                }
+            );
+            assert_eq!(result.total_worked, Duration::HOUR);
         }
     }
 }
@@ -1,74 +1,714 @@
-use lib::{format_date_time, format_time, hours_mins, now, summarize_file, timelog_path};
+mod cli;
+mod config;
+mod reminder;
+mod report;
 
-const UNDEFINED_CHAR_REPRESENTATION: char = '\u{22a5}';
+use anyhow::{bail, Context};
+use cli::Args;
+use config::Config;
+use lib::{
+    decimal_hours_with, end_of_yesterday, export, format_time, json, now, now_at, open_session,
+    read_files, read_schedule, summarize_file, summarize_file_with_progress, summarize_files,
+    timelog_files, timelog_path, Interval, Options, ParseError, Summary, Warning,
+};
+use std::{
+    env, fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    process, thread,
+};
+use time::PrimitiveDateTime;
+
+/// Clears the terminal and moves the cursor to the top left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Returns to the start of the line and clears it, removing the progress.
+const CLEAR_LINE: &str = "\r\x1b[K";
+
+/// The environment variable overriding the comment markers, like
+/// `--comment-chars`.
+const COMMENT_CHARS_ENV_VAR_NAME: &str = "TLSUM_COMMENT_CHARS";
+
+/// The files of `--timelog-dir` are matched against this pattern.
+const TIMELOG_DIR_PATTERN: &str = "timelog-*";
+
+/// Where to read the timelog from.
+enum Timelog {
+    File(PathBuf),
+    /// A directory of timelogs, e.g. one per month, summarized as one.
+    Dir(PathBuf),
+}
+
+impl Timelog {
+    /// Summarizes the timelog, with `progress` showing the percentage read
+    /// of a single file on stderr.
+    fn summarize(
+        &self,
+        now: &PrimitiveDateTime,
+        options: &Options,
+        progress: bool,
+    ) -> anyhow::Result<Summary> {
+        match self {
+            Self::File(path) if progress => {
+                let mut shown = None;
+                let summary = summarize_file_with_progress(path, now, options, |read, size| {
+                    let percentage = read * 100 / size.max(1);
+                    if shown != Some(percentage) {
+                        shown = Some(percentage);
+                        eprint!("\rtlsum: reading {}%", percentage);
+                    }
+                });
+                eprint!("{}", CLEAR_LINE);
+                summary
+            }
+            Self::File(path) => summarize_file(path, now, options),
+            Self::Dir(dir) => {
+                summarize_files(&timelog_files(dir, TIMELOG_DIR_PATTERN)?, now, options)
+            }
+        }
+    }
+
+    /// Returns the open session at the end of the timelog, if any.
+    fn open_session(
+        &self,
+        now: &PrimitiveDateTime,
+        options: &Options,
+    ) -> anyhow::Result<Option<Interval>> {
+        match self {
+            Self::File(path) => {
+                let file = fs::File::open(path)
+                    .with_context(|| format!("unable to read {}", path.to_string_lossy()))?;
+                open_session(io::BufReader::new(file), now, options)
+            }
+            Self::Dir(_) => {
+                let (reader, _) = self.reader(options)?;
+                open_session(reader, now, options)
+            }
+        }
+    }
+
+    /// Opens the timelog for reading as it goes, along with the warnings
+    /// about the files of a directory that were skipped, which are read
+    /// whole up front.
+    fn reader(&self, options: &Options) -> anyhow::Result<(Box<dyn BufRead>, Vec<String>)> {
+        match self {
+            Self::File(path) => {
+                let file = fs::File::open(path)
+                    .with_context(|| format!("unable to read {}", path.to_string_lossy()))?;
+                Ok((Box::new(io::BufReader::new(file)), Vec::new()))
+            }
+            Self::Dir(dir) => {
+                let (contents, warnings) =
+                    read_files(&timelog_files(dir, TIMELOG_DIR_PATTERN)?, options)?;
+                Ok((Box::new(io::Cursor::new(contents)), warnings))
+            }
+        }
+    }
+}
 
 #[allow(clippy::print_stdout)]
 fn main() -> anyhow::Result<()> {
-    let time_log = timelog_path()?;
-    let now = now()?;
-    let summary = summarize_file(time_log, &now)?;
-    let undefined = || Ok(format!("{}", UNDEFINED_CHAR_REPRESENTATION));
-    println!(
-        r"
-{:─<71}
-{:─<71}
-{:<45}{}
-{:<45}{}
-{:<45}{}
-{:─<71}
-{:<45}{}
-{:<45}{}
-{:<45}{:<5} days 
-{:─<71}
-{:<45}{}
-{:<45}{}
-{:<45}{}
-{:<45}{}
-{:<45}{}
-{:<45}{}
-{:─<71}
-{:─<71}",
-        "─",
-        "─",
-        "First punch in today:",
-        summary
-            .first_punchin_today
-            .map_or_else(undefined, format_time)?,
-        "Last punch in:",
-        summary
-            .last_punchin
-            .map_or_else(undefined, format_date_time)?,
-        "Last punch out:",
-        summary
-            .last_punchout
-            .map_or_else(undefined, format_date_time)?,
-        "─",
-        "Average number of hours worked per workday:",
-        summary
-            .avg_worked
-            .map(hours_mins)
-            .unwrap_or_else(|| UNDEFINED_CHAR_REPRESENTATION.to_string()),
-        "Total time worked:",
-        hours_mins(summary.total_worked),
-        "Number of days worked:",
-        summary.num_days_worked,
-        "─",
-        "Cummulative overtime per yesterday:",
-        hours_mins(summary.overtime),
-        "Worked today:",
-        hours_mins(summary.worked_today),
-        "Still to work (8hrs):",
-        hours_mins(summary.still_to_work_8),
-        "Still to work:",
-        hours_mins(summary.still_to_work),
-        "Time to leave (8hrs):",
+    let mut args = Config::load()?.apply(Args::default())?;
+    if let Some(chars) = env::var_os(COMMENT_CHARS_ENV_VAR_NAME) {
+        args.options.comment_chars = chars.to_string_lossy().chars().collect();
+    }
+    let mut args = args.update(env::args().skip(1))?;
+    if let Some(path) = &args.schedule {
+        args.options.schedule = read_schedule(path)?;
+    }
+    let time_log = match &args.timelog_dir {
+        Some(dir) => Timelog::Dir(dir.clone()),
+        None => Timelog::File(args.timelog.clone().map_or_else(timelog_path, Ok)?),
+    };
+    if !args.watch {
+        let output = output(&args, &time_log)?;
+        emit(&args, "", &output)?;
+        if args.from_last_clockin && output == report::CLOCKED_OUT {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    // Ctrl-C terminates the process through the default signal handler,
+    // there is no state to clean up in between iterations.
+    loop {
+        emit(&args, CLEAR_SCREEN, &output(&args, &time_log)?)?;
+        thread::sleep(args.interval.try_into()?);
+    }
+}
+
+/// Prints `output` after `prefix`, or with `--out` writes only `output` to
+/// that file instead, and to both with `--tee`.
+#[allow(clippy::print_stdout)]
+fn emit(args: &Args, prefix: &str, output: &str) -> anyhow::Result<()> {
+    if let Some(path) = &args.out {
+        write_atomically(path, output)?;
+        if !args.tee {
+            return Ok(());
+        }
+    }
+    print!("{}{}", prefix, output);
+    Ok(())
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it to
+/// `path`, so a reader sees either the previous or the new contents whole.
+fn write_atomically(path: &Path, contents: &str) -> anyhow::Result<()> {
+    let name = path
+        .file_name()
+        .with_context(|| format!("not a file: {}", path.to_string_lossy()))?;
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name.to_string_lossy(), process::id()));
+    fs::write(&temp, contents)
+        .with_context(|| format!("unable to write {}", temp.to_string_lossy()))?;
+    fs::rename(&temp, path).or_else(|error| {
+        let _ = fs::remove_file(&temp);
+        Err(error).with_context(|| format!("unable to write {}", path.to_string_lossy()))
+    })
+}
+
+/// Runs once, reporting a failure as JSON on stdout for `--json`.
+#[allow(clippy::print_stdout)]
+fn output(args: &Args, time_log: &Timelog) -> anyhow::Result<String> {
+    match run(args, time_log, &current_time(args)?, &mut io::stderr()) {
+        Err(error) if args.json => {
+            println!("{}", json::error(&error));
+            process::exit(1);
+        }
+        result => result,
+    }
+}
+
+/// The time of `--now` or `--epoch-now`, or else the current time at the
+/// offset of `--tz` or the local one.
+fn current_time(args: &Args) -> anyhow::Result<PrimitiveDateTime> {
+    match (args.now, args.tz) {
+        (Some(now), _) => Ok(now),
+        (None, Some(offset)) => Ok(now_at(offset)),
+        (None, None) => now(),
+    }
+}
+
+/// Summarizes the timelog as of `now`, returning what to print on stdout.
+/// Skipped lines and warnings go to `diagnostics`, keeping the output of
+/// `--matrix` machine readable, or into the object printed by `--json`.
+fn run(
+    args: &Args,
+    time_log: &Timelog,
+    now: &PrimitiveDateTime,
+    diagnostics: &mut dyn Write,
+) -> anyhow::Result<String> {
+    let now = *now;
+    let mut options = args.options.clone();
+    if args.as_of_yesterday {
+        options.as_of = Some(end_of_yesterday(&now));
+    }
+    if args.status {
+        let open = time_log.open_session(&now, &options)?;
+        return Ok(report::render(
+            &report::status_rows(open.as_ref(), args)?,
+            args.width,
+        ));
+    }
+    if args.from_last_clockin {
+        let open = time_log.open_session(&now, &options)?;
+        return Ok(report::since_last_clockin(open.as_ref()));
+    }
+    if args.dump_intervals || args.json_lines || args.hledger {
+        let (reader, warnings) = time_log.reader(&options)?;
+        let mut intervals = options.intervals(reader, now);
+        let selected = intervals.by_ref().filter_map(|interval| {
+            interval
+                .map(|interval| options.select(interval))
+                .transpose()
+        });
+        let dump = if args.json_lines && args.out.is_none() {
+            json::interval_lines(selected, args.output_tz, &mut io::stdout().lock())?;
+            String::new()
+        } else if args.json_lines {
+            // Collected for `emit` to write to the `--out` file at once.
+            let mut lines = Vec::new();
+            json::interval_lines(selected, args.output_tz, &mut lines)?;
+            String::from_utf8(lines)?
+        } else if args.hledger {
+            export::hledger(selected)?
+        } else {
+            export::intervals(selected)?
+        };
+        let warnings: Vec<Warning> = warnings
+            .into_iter()
+            .map(Warning::from)
+            .chain(intervals.warnings().iter().map(Warning::from))
+            .collect();
+        report_diagnostics(&intervals.into_skipped(), &warnings, diagnostics)?;
+        return Ok(dump);
+    }
+    let mut summary = time_log.summarize(&now, &options, args.progress)?;
+    if let Some(limit) = args.legal_max {
+        let over: Vec<String> = summary
+            .days_over(limit)
+            .into_iter()
+            .map(|(date, worked)| {
+                format!(
+                    "{} ({} hours)",
+                    date,
+                    decimal_hours_with(worked, args.decimal_separator)
+                )
+            })
+            .collect();
+        if !over.is_empty() {
+            let message = format!(
+                "exceeded the legal maximum of {} hours on {}",
+                decimal_hours_with(limit, args.decimal_separator),
+                over.join(", ")
+            );
+            if !args.legal_max_warn {
+                bail!(message);
+            }
+            summary.warnings.push(Warning::from(message));
+        }
+    }
+    let machine_readable = args.matrix
+        || args.hour_histogram
+        || args.bill_round_up.is_some()
+        || args.oneline
+        || args.balance_chart
+        || args.timesheet
+        || args.json;
+    if machine_readable && !args.no_reminders && summary.no_entries_today(&now) {
         summary
-            .time_to_leave_8
-            .map_or_else(undefined, format_time)?,
-        "Time to leave:",
-        summary.time_to_leave.map_or_else(undefined, format_time)?,
-        "─",
-        "─",
-    );
+            .warnings
+            .push(Warning::from("no entries yet today".to_owned()));
+    }
+    if args.json {
+        return Ok(format!("{}\n", json::summary(&summary, args.output_tz)));
+    }
+    report_diagnostics(&summary.skipped, &summary.warnings, diagnostics)?;
+    if args.matrix {
+        return Ok(export::matrix(&summary.days, args.decimal_separator));
+    }
+    if args.hour_histogram {
+        return Ok(export::hour_histogram(
+            &summary.hour_histogram,
+            args.decimal_separator,
+        ));
+    }
+    if let Some(increment) = args.bill_round_up {
+        return Ok(export::billing(
+            &summary.days,
+            increment,
+            args.decimal_separator,
+        ));
+    }
+    if args.oneline {
+        return report::oneline(&summary, args);
+    }
+    if args.timesheet {
+        return report::timesheet(&summary, summary.today);
+    }
+    if args.balance_chart {
+        return Ok(report::balance_chart(&summary, args));
+    }
+    if let Some(path) = &args.reminder {
+        reminder::remind(
+            path,
+            summary.clocked_in,
+            summary.still_to_work,
+            args.reminder_window,
+            summary.today,
+        )?;
+    }
+    let mut text = report::text(&summary, args, &now)?;
+    if let Some(leave) = args.plan_leave {
+        let planned = PrimitiveDateTime::new(summary.today, leave);
+        check_plan_leave(&summary, planned)?;
+        // As of yesterday the plan leaves out what came after leaving then.
+        let options = Options {
+            as_of: options.as_of.map(|_| planned),
+            ..options.clone()
+        };
+        let plan = time_log
+            .summarize(&planned, &options, false)?
+            .leave_plan(summary.today, &options);
+        text.push_str(&report::render(
+            &report::plan_rows(&plan, args, leave)?,
+            args.width,
+        ));
+    }
+    if let Some(path) = &args.baseline {
+        let baseline = json::load_summary(path)?;
+        for delta in json::baseline_deltas(&baseline, &(&summary).into()) {
+            text.push_str(&delta);
+            text.push('\n');
+        }
+    }
+    Ok(text)
+}
+
+/// Fails when `planned` is before the open clock in or, when clocked out,
+/// before the last clock out, a moment the timelog has moved past.
+fn check_plan_leave(summary: &Summary, planned: PrimitiveDateTime) -> anyhow::Result<()> {
+    let (last, what) = if summary.clocked_in {
+        (summary.last_punchin, "clock in")
+    } else {
+        (summary.last_punchout, "clock out")
+    };
+    match last.filter(|last| planned < *last) {
+        Some(last) => bail!(
+            "[--plan-leave] {} is before the last {} at {}",
+            format_time(planned)?,
+            what,
+            format_time(last)?
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Writes the skipped lines and warnings, followed by a count of both when
+/// there are any.
+fn report_diagnostics(
+    skipped: &[ParseError],
+    warnings: &[Warning],
+    diagnostics: &mut dyn Write,
+) -> io::Result<()> {
+    for skipped in skipped {
+        writeln!(diagnostics, "tlsum: skipped {}", skipped)?;
+    }
+    for warning in warnings {
+        writeln!(diagnostics, "tlsum: warning: {}", warning)?;
+    }
+    if !skipped.is_empty() || !warnings.is_empty() {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        writeln!(
+            diagnostics,
+            "tlsum: {} warning{}, {} skipped line{}",
+            warnings.len(),
+            plural(warnings.len()),
+            skipped.len(),
+            plural(skipped.len())
+        )?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use time::macros::datetime;
+
+    #[test]
+    fn run_should_fail_on_day_over_legal_max_unless_warning_only() {
+        let path = env::temp_dir().join(format!("tlsum-legal-max-{}", process::id()));
+        fs::write(
+            &path,
+            "i 2022/01/03 08:00:00 acme\no 2022/01/03 16:00:00\n\
+             i 2022/01/04 07:00:00 acme\no 2022/01/04 20:00:00\n",
+        )
+        .unwrap();
+        let args = Args {
+            legal_max: Some(time::Duration::hours(12)),
+            no_reminders: true,
+            ..Args::default()
+        };
+        let time_log = Timelog::File(path.clone());
+        let now = datetime!(2022-01-05 10:00:00);
+        let error = run(&args, &time_log, &now, &mut io::sink()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "exceeded the legal maximum of 12.00 hours on 2022-01-04 (13.00 hours)"
+        );
+        let args = Args {
+            legal_max_warn: true,
+            ..args
+        };
+        let mut diagnostics = Vec::new();
+        let result = run(&args, &time_log, &now, &mut diagnostics);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_ok());
+        assert!(String::from_utf8(diagnostics)
+            .unwrap()
+            .contains("tlsum: warning: exceeded the legal maximum"));
+    }
+
+    #[test]
+    fn run_should_reject_plan_leave_before_last_clock_in() {
+        let path = env::temp_dir().join(format!("tlsum-plan-leave-{}", process::id()));
+        fs::write(
+            &path,
+            "i 2022/01/04 09:00:00 acme\no 2022/01/04 17:00:00\n\
+             i 2022/01/05 13:00:00 acme\n",
+        )
+        .unwrap();
+        let time_log = Timelog::File(path.clone());
+        let now = datetime!(2022-01-05 14:00:00);
+        let args = |leave| Args {
+            plan_leave: Some(leave),
+            no_reminders: true,
+            ..Args::default()
+        };
+        let early = run(
+            &args(time::macros::time!(12:30)),
+            &time_log,
+            &now,
+            &mut io::sink(),
+        );
+        let late = run(
+            &args(time::macros::time!(17:00)),
+            &time_log,
+            &now,
+            &mut io::sink(),
+        );
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            early.unwrap_err().to_string(),
+            "[--plan-leave] 12:30:00 is before the last clock in at 13:00:00"
+        );
+        assert!(late.is_ok());
+    }
+
+    #[test]
+    fn run_should_dump_intervals_of_the_selected_accounts_only() {
+        let path = env::temp_dir().join(format!("tlsum-dump-accounts-{}", process::id()));
+        fs::write(
+            &path,
+            "i 2022/01/04 09:00:00 e:acme:dev\no 2022/01/04 12:00:00\n\
+             i 2022/01/04 13:00:00 e:lunch\no 2022/01/04 13:30:00\n",
+        )
+        .unwrap();
+        let mut args = Args {
+            dump_intervals: true,
+            no_reminders: true,
+            ..Args::default()
+        };
+        args.options.strip_prefixes = vec!["e:".to_owned()];
+        args.options.exclude_accounts = vec!["lunch".to_owned()];
+        let time_log = Timelog::File(path.clone());
+        let now = datetime!(2022-01-05 10:00:00);
+        let dump = run(&args, &time_log, &now, &mut io::sink());
+        fs::remove_file(&path).unwrap();
+        let dump = dump.unwrap();
+        assert!(dump.contains("acme:dev"));
+        assert!(!dump.contains("e:acme"));
+        assert!(!dump.contains("lunch"));
+    }
+
+    #[test]
+    fn run_should_plan_leave_as_of_yesterday_on_yesterday() {
+        let path = env::temp_dir().join(format!("tlsum-plan-yesterday-{}", process::id()));
+        fs::write(
+            &path,
+            "i 2022/01/04 09:00:00 acme\no 2022/01/04 12:00:00\n\
+             i 2022/01/05 09:00:00 acme\no 2022/01/05 10:00:00\n",
+        )
+        .unwrap();
+        let args = Args {
+            as_of_yesterday: true,
+            plan_leave: Some(time::macros::time!(17:00)),
+            no_reminders: true,
+            ..Args::default()
+        };
+        let now = datetime!(2022-01-05 11:00:00);
+        let text = run(&args, &Timelog::File(path.clone()), &now, &mut io::sink());
+        let timesheet = Args {
+            timesheet: true,
+            ..args
+        };
+        let timesheet = run(
+            &timesheet,
+            &Timelog::File(path.clone()),
+            &now,
+            &mut io::sink(),
+        );
+        fs::remove_file(&path).unwrap();
+        assert!(text.unwrap().contains(&format!(
+            "{:<45}3     hours, 0     minutes",
+            "Worked today leaving at 17:00:"
+        )));
+        assert!(timesheet
+            .unwrap()
+            .starts_with("Timesheet 2022-01-03 - 2022-01-09"));
+    }
+
+    #[test]
+    fn run_should_dump_the_intervals_behind_the_summary() {
+        let path = env::temp_dir().join(format!("tlsum-dump-summary-{}", process::id()));
+        fs::write(
+            &path,
+            "i 2022/01/03 09:00:00 acme\no 2022/01/03 17:00:00\n\
+             i 2022/01/04 09:00:00 acme\no 2022/01/04 12:00:00\n\
+             i 2022/01/04 13:00:00 acme\n",
+        )
+        .unwrap();
+        let time_log = Timelog::File(path.clone());
+        let now = datetime!(2022-01-05 10:00:00);
+        let args = |dump_intervals, hledger| {
+            let mut args = Args {
+                dump_intervals,
+                hledger,
+                no_reminders: true,
+                ..Args::default()
+            };
+            args.options.no_open_session = true;
+            args
+        };
+        let dump = run(&args(true, false), &time_log, &now, &mut io::sink());
+        let hledger = run(&args(false, true), &time_log, &now, &mut io::sink());
+        let summary = time_log.summarize(&now, &args(false, false).options, false);
+        fs::remove_file(&path).unwrap();
+        let dump = dump.unwrap();
+        assert_eq!(dump.lines().count(), 2);
+        assert!(!dump.contains("open"));
+        assert_eq!(summary.unwrap().total_worked, time::Duration::hours(11));
+        assert_eq!(hledger.unwrap().matches("(acme)").count(), 2);
+    }
+
+    #[test]
+    fn epoch_now_should_end_an_open_session() {
+        let path = env::temp_dir().join(format!("tlsum-epoch-now-{}", process::id()));
+        fs::write(&path, "i 1650618000 acme\n").unwrap();
+        let args = Args::parse(["--epoch-now".to_owned(), "1650623400".to_owned()]).unwrap();
+        let now = current_time(&args).unwrap();
+        let summary = Timelog::File(path.clone()).summarize(&now, &args.options, false);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(summary.unwrap().worked_today, time::Duration::minutes(90));
+    }
+
+    #[test]
+    fn run_should_print_only_elapsed_time_from_last_clockin() {
+        let path = env::temp_dir().join(format!("tlsum-last-clockin-{}", process::id()));
+        fs::write(&path, "i 2022/01/04 09:00:00 acme\no 2022/01/04 12:00:00\n").unwrap();
+        let args = Args {
+            from_last_clockin: true,
+            ..Args::default()
+        };
+        let time_log = Timelog::File(path.clone());
+        let now = datetime!(2022-01-04 14:20:00);
+        let clocked_out = run(&args, &time_log, &now, &mut io::sink()).unwrap();
+        fs::write(&path, "i 2022/01/04 13:00:00 acme\n").unwrap();
+        let clocked_in = run(&args, &time_log, &now, &mut io::sink()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(clocked_out, report::CLOCKED_OUT);
+        assert_eq!(clocked_in, "1h20m\n");
+    }
+
+    #[test]
+    fn emit_should_replace_out_file_without_leaving_temp_file() {
+        let dir = env::temp_dir().join(format!("tlsum-out-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("timelog");
+        fs::write(&log, "i 2022/01/04 09:00:00 acme\no 2022/01/04 17:00:00\n").unwrap();
+        let out = dir.join("status.txt");
+        fs::write(&out, "stale").unwrap();
+        let args = Args {
+            out: Some(out.clone()),
+            no_reminders: true,
+            ..Args::default()
+        };
+        let now = datetime!(2022-01-05 10:00:00);
+        let report = run(&args, &Timelog::File(log), &now, &mut io::sink()).unwrap();
+        emit(&args, CLEAR_SCREEN, &report).unwrap();
+        let written = fs::read_to_string(&out).unwrap();
+        let entries = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(written, report);
+        assert!(written.contains("Total time worked:"));
+        assert_eq!(entries, 2);
+    }
+
+    #[test]
+    fn emit_should_write_json_lines_to_out_file() {
+        let dir = env::temp_dir().join(format!("tlsum-out-json-lines-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("timelog");
+        fs::write(&log, "i 2022/01/04 09:00:00 acme\no 2022/01/04 17:00:00\n").unwrap();
+        let out = dir.join("intervals.jsonl");
+        let args = Args {
+            json_lines: true,
+            out: Some(out.clone()),
+            no_reminders: true,
+            ..Args::default()
+        };
+        let now = datetime!(2022-01-05 10:00:00);
+        let lines = run(&args, &Timelog::File(log), &now, &mut io::sink()).unwrap();
+        emit(&args, "", &lines).unwrap();
+        let written = fs::read_to_string(&out).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(written, lines);
+        assert!(written.starts_with("{\"start\":\"2022-01-04T09:00:00\""));
+        assert_eq!(written.lines().count(), 1);
+    }
+
+    #[test]
+    fn run_should_leave_out_json_lines_after_as_of() {
+        let path = env::temp_dir().join(format!("tlsum-json-lines-as-of-{}", process::id()));
+        fs::write(
+            &path,
+            "i 2022/01/04 09:00:00 acme\no 2022/01/04 17:00:00\n\
+             i 2022/01/05 09:00:00 acme\n",
+        )
+        .unwrap();
+        let args = Args {
+            json_lines: true,
+            as_of_yesterday: true,
+            out: Some(env::temp_dir().join("unused.jsonl")),
+            no_reminders: true,
+            ..Args::default()
+        };
+        let now = datetime!(2022-01-05 10:00:00);
+        let lines = run(&args, &Timelog::File(path.clone()), &now, &mut io::sink());
+        fs::remove_file(&path).unwrap();
+        let lines = lines.unwrap();
+        assert_eq!(lines.lines().count(), 1);
+        assert!(lines.contains("\"end\":\"2022-01-04T17:00:00\""));
+    }
+
+    #[test]
+    fn run_should_put_diagnostics_in_json_output_only() {
+        let path = env::temp_dir().join(format!("tlsum-diagnostics-{}", process::id()));
+        fs::write(
+            &path,
+            "i 2022/01/04 09:00:00 acme\no 2022/01/04 17:00:00\n\
+             x 2022/01/04 18:00:00\n",
+        )
+        .unwrap();
+        let args = Args {
+            json: true,
+            options: Options {
+                lenient: true,
+                ..Options::default()
+            },
+            ..Args::default()
+        };
+        let time_log = Timelog::File(path.clone());
+        let mut diagnostics = Vec::new();
+        let now = datetime!(2022-01-05 10:00:00);
+        let stdout = run(&args, &time_log, &now, &mut diagnostics).unwrap();
+        let json: json::SummaryJson = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(json.total_worked_seconds, 8 * 3600);
+        assert_eq!(
+            json.warnings
+                .iter()
+                .map(|warning| (warning.line, warning.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(3), "unknown clock type: [x]"),
+                (None, "no entries yet today")
+            ]
+        );
+        assert!(diagnostics.is_empty());
+
+        let args = Args {
+            json: false,
+            matrix: true,
+            ..args
+        };
+        let stdout = run(&args, &time_log, &now, &mut diagnostics).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(stdout.starts_with("date,acme\n"));
+        let stderr = String::from_utf8(diagnostics).unwrap();
+        assert_eq!(
+            stderr.lines().last(),
+            Some("tlsum: 1 warning, 1 skipped line")
+        );
+        assert!(stderr.contains("tlsum: warning: no entries yet today"));
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+use time::{Date, Duration};
+
+/// Writes today's date to the sentinel file at `path` when clocked in with
+/// at most `window` left to work, so a cron job or notification hook can
+/// watch for it. The file is written at most once per day. Returns whether
+/// the sentinel was written.
+pub fn remind(
+    path: &Path,
+    clocked_in: bool,
+    still_to_work: Duration,
+    window: Duration,
+    today: Date,
+) -> Result<bool> {
+    if !clocked_in || still_to_work > window {
+        return Ok(false);
+    }
+    let stamp = today.to_string();
+    if fs::read_to_string(path).is_ok_and(|contents| contents.trim() == stamp) {
+        return Ok(false);
+    }
+    fs::write(path, stamp)
+        .with_context(|| format!("unable to write reminder {}", path.to_string_lossy()))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+    use time::macros::date;
+
+    fn sentinel(name: &str) -> std::path::PathBuf {
+        let path = env::temp_dir().join(format!("tlsum-{}-{}", name, process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn should_not_remind_outside_window() {
+        let path = sentinel("outside");
+        let today = date!(2022 - 01 - 01);
+        let written = remind(
+            &path,
+            true,
+            Duration::minutes(20),
+            Duration::minutes(15),
+            today,
+        )
+        .unwrap();
+        assert!(!written);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn should_not_remind_when_clocked_out() {
+        let path = sentinel("clocked-out");
+        let today = date!(2022 - 01 - 01);
+        let written = remind(&path, false, Duration::ZERO, Duration::minutes(15), today).unwrap();
+        assert!(!written);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn should_remind_once_per_day_inside_window() {
+        let path = sentinel("inside");
+        let today = date!(2022 - 01 - 01);
+        let window = Duration::minutes(15);
+        assert!(remind(&path, true, Duration::minutes(10), window, today).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2022-01-01");
+        assert!(!remind(&path, true, Duration::minutes(5), window, today).unwrap());
+        let tomorrow = date!(2022 - 01 - 02);
+        assert!(remind(&path, true, Duration::minutes(5), window, tomorrow).unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+}
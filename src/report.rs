@@ -0,0 +1,1096 @@
+//! Renders the summary as a text table.
+
+use crate::cli::Args;
+use anyhow::{bail, Result};
+use lib::{
+    compact_hours_mins, format_date_time, format_time, read_dates, relative_date_label,
+    rollup_by_regex, truncate_to_minute, DurationFormat, Interval, LeavePlan, OvertimeThrough,
+    Summary, UNASSIGNED,
+};
+use regex::Regex;
+use std::{borrow::Cow, cmp::min, fmt::Write};
+use time::{
+    format_description::FormatItem, macros::format_description, Date, Duration, Month,
+    PrimitiveDateTime, Time,
+};
+
+pub const DEFAULT_WIDTH: usize = 71;
+/// The width of the label column in the default table.
+const DEFAULT_LABEL_WIDTH: usize = 45;
+const UNDEFINED_CHAR_REPRESENTATION: char = '\u{22a5}';
+const HOUR_MINUTE_FORMAT: &[FormatItem<'static>] = format_description!("[hour]:[minute]");
+
+/// A line of the table.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Row {
+    Rule,
+    Field(String, String),
+}
+
+impl Row {
+    fn field(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Field(label.into(), value.into())
+    }
+}
+
+fn undefined() -> Result<String> {
+    Ok(UNDEFINED_CHAR_REPRESENTATION.to_string())
+}
+
+/// Formats a duration the way the arguments ask for.
+pub fn duration(args: &Args, duration: Duration) -> String {
+    let format = if args.compact {
+        DurationFormat::Compact
+    } else if args.with_seconds {
+        DurationFormat::WithSeconds
+    } else if args.group_digits {
+        DurationFormat::Grouped(args.group_separator)
+    } else {
+        DurationFormat::HoursMins
+    };
+    args.negative_style
+        .render(duration, |duration| format.format(duration))
+}
+
+/// Formats a duration like [`duration`], followed by the number of workdays
+/// of the target in effect on `today` it amounts to for `--in-days`, e.g.
+/// `(10.0 days)`.
+pub fn duration_in_days(args: &Args, today: Date, worked: Duration) -> String {
+    let formatted = duration(args, worked);
+    let target = args.options.target_on(today);
+    if args.in_days && target.is_positive() {
+        format!("{} ({:.1} days)", formatted, worked / target)
+    } else {
+        formatted
+    }
+}
+
+/// The sections of the summary table that `--sections` selects from.
+pub const SECTIONS: [&str; 23] = [
+    "first-in",
+    "last-in",
+    "last-out",
+    "avg-worked",
+    "stddev-worked",
+    "total",
+    "recorded",
+    "billable",
+    "non-billable",
+    "projected-month",
+    "days-worked",
+    "sessions",
+    "avg-session",
+    "overtime",
+    "uncounted-overtime",
+    "recomputed-overtime",
+    "worked-today",
+    "focus-ratio",
+    "still-to-work-contract",
+    "still-to-work",
+    "still-to-work-week",
+    "leave-contract",
+    "leave",
+];
+
+/// The default summary table, `-` being a rule.
+const DEFAULT_LAYOUT: [&str; 29] = [
+    "-",
+    "-",
+    "first-in",
+    "last-in",
+    "last-out",
+    "-",
+    "avg-worked",
+    "stddev-worked",
+    "total",
+    "recorded",
+    "billable",
+    "non-billable",
+    "projected-month",
+    "days-worked",
+    "sessions",
+    "avg-session",
+    "-",
+    "overtime",
+    "uncounted-overtime",
+    "recomputed-overtime",
+    "worked-today",
+    "focus-ratio",
+    "still-to-work-contract",
+    "still-to-work",
+    "still-to-work-week",
+    "leave-contract",
+    "leave",
+    "-",
+    "-",
+];
+
+/// The row of the section `name` of [`SECTIONS`], or `-` for a rule. The
+/// billable, uncounted and recomputed figures are left out unless the
+/// arguments ask for them, the recorded total unless an open session makes
+/// it differ from the total.
+fn section(summary: &Summary, args: &Args, name: &str) -> Result<Option<Row>> {
+    let contract_hours = args
+        .options
+        .contract
+        .unwrap_or_else(|| args.options.target_on(summary.today))
+        .as_seconds_f64()
+        / 3600.0;
+    let duration = |d| duration(args, d);
+    let undefined_or = |d: Option<Duration>| {
+        d.map(duration)
+            .unwrap_or_else(|| UNDEFINED_CHAR_REPRESENTATION.to_string())
+    };
+    let row = match name {
+        "-" => Row::Rule,
+        "first-in" => Row::field(
+            "First punch in today:",
+            summary
+                .first_punchin_today
+                .map_or_else(undefined, format_time)?,
+        ),
+        "last-in" => Row::field(
+            "Last punch in:",
+            summary
+                .last_punchin
+                .map_or_else(undefined, format_date_time)?,
+        ),
+        "last-out" => Row::field(
+            "Last punch out:",
+            summary
+                .last_punchout
+                .map_or_else(undefined, format_date_time)?,
+        ),
+        "avg-worked" => Row::field(
+            "Average number of hours worked per workday:",
+            undefined_or(summary.avg_worked),
+        ),
+        "stddev-worked" => Row::field(
+            "Std dev of daily hours:",
+            undefined_or(summary.stddev_worked),
+        ),
+        "total" => Row::field(
+            "Total time worked:",
+            duration_in_days(args, summary.today, summary.total_worked),
+        ),
+        "recorded" if summary.recorded_total == summary.total_worked => return Ok(None),
+        "recorded" => Row::field(
+            "Recorded (closed) total:",
+            duration_in_days(args, summary.today, summary.recorded_total),
+        ),
+        "billable" | "non-billable" if args.options.billable.is_empty() => return Ok(None),
+        "billable" => Row::field("Billable:", duration(summary.billable)),
+        "non-billable" => Row::field("Non-billable:", duration(summary.non_billable)),
+        "projected-month" => Row::field(
+            "Projected month total:",
+            undefined_or(summary.projected_month),
+        ),
+        "days-worked" => Row::field(
+            "Number of days worked:",
+            format!("{:<5} days ", summary.num_days_worked),
+        ),
+        "sessions" => Row::field("Sessions:", summary.num_sessions.to_string()),
+        "avg-session" => Row::field("Average session length:", undefined_or(summary.avg_session)),
+        "overtime" => Row::field(
+            match args.options.overtime_through {
+                OvertimeThrough::Yesterday => "Cummulative overtime per yesterday:",
+                OvertimeThrough::Today => "Cummulative overtime per today:",
+            },
+            format!(
+                "{} ({})",
+                duration_in_days(args, summary.today, summary.overtime),
+                summary.overtime_ratio(&args.options).map_or_else(
+                    || "n/a".to_owned(),
+                    |ratio| format!("{:+.1}% of expected", ratio * 100.0)
+                )
+            ),
+        ),
+        "uncounted-overtime" if !args.options.cap_daily => return Ok(None),
+        "uncounted-overtime" => {
+            Row::field("Uncounted overtime:", duration(summary.uncounted_overtime))
+        }
+        "recomputed-overtime" => match args.recompute_target {
+            Some(target) => Row::field(
+                format!(
+                    "Cummulative overtime at {}hrs:",
+                    target.as_seconds_f64() / 3600.0
+                ),
+                duration_in_days(
+                    args,
+                    summary.today,
+                    summary.overtime_at(target, &args.options),
+                ),
+            ),
+            None => return Ok(None),
+        },
+        "worked-today" => Row::field("Worked today:", duration(summary.worked_today)),
+        "focus-ratio" => Row::field(
+            "Focus ratio today:",
+            summary.focus_ratio_today.map_or_else(
+                || UNDEFINED_CHAR_REPRESENTATION.to_string(),
+                |ratio| format!("{:.0}%", ratio * 100.0),
+            ),
+        ),
+        "still-to-work-contract" => Row::field(
+            format!("Still to work ({}hrs):", contract_hours),
+            duration(summary.still_to_work_8),
+        ),
+        "still-to-work" => Row::field("Still to work:", duration(summary.still_to_work)),
+        "still-to-work-week" => Row::field(
+            "Still to work this week:",
+            duration(summary.still_to_work_week),
+        ),
+        "leave-contract" => Row::field(
+            format!("Time to leave ({}hrs):", contract_hours),
+            summary
+                .time_to_leave_8
+                .map_or_else(undefined, format_leave)?,
+        ),
+        "leave" => Row::field(
+            "Time to leave:",
+            summary.time_to_leave.map_or_else(undefined, format_leave)?,
+        ),
+        other => bail!(
+            "unknown section: [{}], expected one of {}",
+            other,
+            SECTIONS.join(", ")
+        ),
+    };
+    Ok(Some(row))
+}
+
+/// The rows of the summary table, the sections of `--sections` in their
+/// order when given.
+pub fn summary_rows(summary: &Summary, args: &Args) -> Result<Vec<Row>> {
+    let layout: Vec<&str> = args.sections.as_ref().map_or_else(
+        || DEFAULT_LAYOUT.to_vec(),
+        |names| names.iter().map(String::as_str).collect(),
+    );
+    let mut rows = Vec::new();
+    for name in layout {
+        rows.extend(section(summary, args, name)?);
+    }
+    Ok(rows)
+}
+
+/// The rows listing the time worked per account, sorted by account, along
+/// with the date it was last worked on.
+pub fn project_rows(summary: &Summary, args: &Args, today: Date) -> Vec<Row> {
+    let mut projects: Vec<_> = summary.projects.iter().collect();
+    projects.sort();
+    let mut rows: Vec<Row> = projects
+        .into_iter()
+        .map(|(project, worked)| {
+            let worked = duration(args, *worked);
+            match summary.last_worked.get(project) {
+                Some(&last) => Row::field(
+                    project,
+                    format!(
+                        "{} \u{2014} last worked {}",
+                        worked,
+                        last_worked(last, today)
+                    ),
+                ),
+                None => Row::field(project, worked),
+            }
+        })
+        .collect();
+    rows.push(Row::Rule);
+    rows
+}
+
+/// Formats a last worked date like `2022-03-01 (52 days ago)`.
+fn last_worked(last: Date, today: Date) -> String {
+    match (today - last).whole_days() {
+        0 => format!("{} (today)", last),
+        1 => format!("{} (1 day ago)", last),
+        days => format!("{} ({} days ago)", last, days),
+    }
+}
+
+/// The rows listing the first punch in and the time worked per day.
+pub fn day_rows(summary: &Summary, args: &Args, today: Date) -> Result<Vec<Row>> {
+    let mut rows = Vec::with_capacity(summary.days.len() + 1);
+    for (date, day) in &summary.days {
+        rows.push(Row::field(
+            relative_date_label(*date, today),
+            format!(
+                "{}  {}",
+                format_time(day.first_punchin)?,
+                duration(args, day.worked)
+            ),
+        ));
+    }
+    rows.push(Row::Rule);
+    Ok(rows)
+}
+
+/// The rows listing the time worked per year for `--years`, fiscal years
+/// being labeled like `2022/23`.
+pub fn year_rows(summary: &Summary, args: &Args) -> Vec<Row> {
+    let start = args.fiscal_year_start;
+    let mut rows: Vec<Row> = summary
+        .yearly_totals(start)
+        .into_iter()
+        .map(|(year, worked)| {
+            let label = if start == Month::January {
+                year.to_string()
+            } else {
+                format!("{}/{:02}", year, (year + 1) % 100)
+            };
+            Row::field(label, duration(args, worked))
+        })
+        .collect();
+    rows.push(Row::Rule);
+    rows
+}
+
+/// The rows of `--part-of-day`, the time worked in the morning, the
+/// afternoon and the evening.
+pub fn part_of_day_rows(summary: &Summary, args: &Args) -> Vec<Row> {
+    let parts = summary.parts_of_day();
+    vec![
+        Row::field("Morning (before 12:00):", duration(args, parts.morning)),
+        Row::field("Afternoon (12:00-18:00):", duration(args, parts.afternoon)),
+        Row::field("Evening (after 18:00):", duration(args, parts.evening)),
+        Row::Rule,
+    ]
+}
+
+/// Formats a projected time to leave in whole minutes, e.g. `17:33` rather
+/// than the `17:33:47` carried over from the seconds of now.
+fn format_leave(leave: PrimitiveDateTime) -> Result<String> {
+    Ok(truncate_to_minute(leave).format(HOUR_MINUTE_FORMAT)?)
+}
+
+/// The rows of `--plan-leave`, the figures of clocking out at `leave`.
+pub fn plan_rows(plan: &LeavePlan, args: &Args, leave: Time) -> Result<Vec<Row>> {
+    let at = leave.format(HOUR_MINUTE_FORMAT)?;
+    Ok(vec![
+        Row::field(
+            format!("Worked today leaving at {}:", at),
+            duration(args, plan.worked_today),
+        ),
+        Row::field(
+            format!("Overtime today leaving at {}:", at),
+            duration(args, plan.day_overtime),
+        ),
+        Row::field(
+            format!("Overtime balance leaving at {}:", at),
+            duration(args, plan.balance),
+        ),
+        Row::Rule,
+    ])
+}
+
+/// The rows listing the time worked per group of accounts of `--group-by`.
+pub fn group_rows(summary: &Summary, args: &Args, pattern: &Regex) -> Vec<Row> {
+    let mut rows: Vec<Row> = rollup_by_regex(summary, pattern)
+        .into_iter()
+        .map(|(group, worked)| Row::field(group, duration(args, worked)))
+        .collect();
+    rows.push(Row::Rule);
+    rows
+}
+
+/// Renders the rows as a table of `width` characters, the label column
+/// taking up the same share of the width as in the default table. A label
+/// too long for that column is cut short with an ellipsis.
+pub fn render(rows: &[Row], width: usize) -> String {
+    let label_width = width * DEFAULT_LABEL_WIDTH / DEFAULT_WIDTH;
+    let mut table = String::new();
+    for row in rows {
+        let _ = match row {
+            Row::Rule => writeln!(table, "{:─<width$}", "─", width = width),
+            Row::Field(label, value) => writeln!(
+                table,
+                "{:<width$}{}",
+                fit_label(label, label_width),
+                value,
+                width = label_width
+            ),
+        };
+    }
+    table
+}
+
+/// The label as is when it leaves a space in a column of `width`
+/// characters, else cut short to fit with an ellipsis.
+fn fit_label(label: &str, width: usize) -> Cow<'_, str> {
+    if label.chars().count() < width {
+        return Cow::Borrowed(label);
+    }
+    let mut fitted: String = label.chars().take(width.saturating_sub(2)).collect();
+    fitted.push('\u{2026}');
+    Cow::Owned(fitted)
+}
+
+/// The rows of `tlsum status`: whether clocked in and, when so, the account,
+/// the clock in and the time elapsed since, of the `open` session.
+pub fn status_rows(open: Option<&Interval>, args: &Args) -> Result<Vec<Row>> {
+    let Some(session) = open else {
+        return Ok(vec![Row::field("Clocked in:", "no")]);
+    };
+    let account = if session.account.is_empty() {
+        UNASSIGNED
+    } else {
+        &session.account
+    };
+    Ok(vec![
+        Row::field("Clocked in:", "yes"),
+        Row::field("Account:", account),
+        Row::field("Since:", format_date_time(session.start)?),
+        Row::field("Elapsed:", duration(args, session.duration())),
+    ])
+}
+
+/// The output of `--from-last-clockin` when not clocked in.
+pub const CLOCKED_OUT: &str = "clocked out\n";
+
+/// The time elapsed since the clock in of the open session for
+/// `--from-last-clockin`, e.g. `1h05m`, or [`CLOCKED_OUT`] without one.
+pub fn since_last_clockin(open: Option<&Interval>) -> String {
+    open.map_or_else(
+        || CLOCKED_OUT.to_owned(),
+        |session| format!("{}\n", tight_hours_mins(session.duration(), false)),
+    )
+}
+
+/// A duration without spaces for `--oneline`, e.g. `6h12m`, signed when
+/// `signed`, e.g. `+0h12m`.
+fn tight_hours_mins(duration: Duration, signed: bool) -> String {
+    let sign = match (duration.is_negative(), signed) {
+        (true, _) => "-",
+        (false, true) => "+",
+        (false, false) => "",
+    };
+    let duration = duration.abs();
+    let hours = duration.whole_hours();
+    let minutes = (duration - Duration::hours(hours)).whole_minutes();
+    format!("{}{}h{:02}m", sign, hours, minutes)
+}
+
+/// A single line for a status bar: the time worked today, the balance
+/// including today, see [`Summary::total_overtime_including_today`], and,
+/// when clocked in, the time to leave, e.g.
+/// `▶ 6h12m | bal -1h48m | leave 17:33`.
+pub fn oneline(summary: &Summary, args: &Args) -> Result<String> {
+    let mut line = format!(
+        "{} {} | bal {}",
+        if summary.clocked_in {
+            '\u{25b6}'
+        } else {
+            '\u{23f8}'
+        },
+        tight_hours_mins(summary.worked_today, false),
+        tight_hours_mins(summary.total_overtime_including_today(&args.options), true)
+    );
+    if let Some(leave) = summary.time_to_leave.filter(|_| summary.clocked_in) {
+        let _ = write!(line, " | leave {}", format_leave(leave)?);
+    }
+    line.push('\n');
+    Ok(line)
+}
+
+/// A duration as hours and minutes for `--timesheet`, e.g. `8:05`.
+fn clock_hours_mins(duration: Duration) -> String {
+    let hours = duration.whole_hours();
+    let minutes = (duration - Duration::hours(hours)).whole_minutes();
+    format!("{}:{:02}", hours, minutes)
+}
+
+/// The weekly timesheet of `--timesheet` for the week, Monday up to Sunday,
+/// of `today`: a row per day with its first clock in, last clock out and
+/// time worked, blank for a day without work, and the week's total.
+pub fn timesheet(summary: &Summary, today: Date) -> Result<String> {
+    let monday = today - Duration::days(today.weekday().number_days_from_monday().into());
+    let sunday = monday + Duration::days(6);
+    let mut sheet = format!("Timesheet {} - {}\n", monday, sunday);
+    let _ = writeln!(
+        sheet,
+        "{:<5}{:<12}{:<7}{:<7}{:>6}",
+        "Day", "Date", "In", "Out", "Total"
+    );
+    let mut total = Duration::ZERO;
+    for date in (0..7).map(|offset| monday + Duration::days(offset)) {
+        let weekday = date.weekday().to_string();
+        let mut row = format!("{:<5}{:<12}", &weekday[..3], date.to_string());
+        if let Some(day) = summary.days.get(&date) {
+            total += day.worked;
+            let _ = write!(
+                row,
+                "{:<7}{:<7}{:>6}",
+                day.first_punchin.format(HOUR_MINUTE_FORMAT)?,
+                day.last_end.format(HOUR_MINUTE_FORMAT)?,
+                clock_hours_mins(day.worked)
+            );
+        }
+        sheet.push_str(row.trim_end());
+        sheet.push('\n');
+    }
+    let _ = writeln!(sheet, "{:<31}{:>6}", "Total", clock_hours_mins(total));
+    Ok(sheet)
+}
+
+/// The lines of `--balance-chart`, every worked day with its deviation from
+/// the target and the running total, e.g. `2022-04-20  +0h30m  (cum +0h30m)`.
+pub fn balance_chart(summary: &Summary, args: &Args) -> String {
+    let mut chart = String::new();
+    for day in summary.daily_balance(&args.options) {
+        let _ = writeln!(
+            chart,
+            "{}  {}  (cum {})",
+            day.date,
+            tight_hours_mins(day.deviation, true),
+            tight_hours_mins(day.balance, true)
+        );
+    }
+    chart
+}
+
+/// The derivation of the overtime and the time still to work for
+/// `--explain`, with the figures of `summary`.
+pub fn explain(summary: &Summary, args: &Args) -> String {
+    let target = args.options.target_on(summary.today);
+    let expected_days = summary.expected_days(&args.options);
+    let expected = summary.expected_hours(&args.options);
+    let per_day = if args.options.schedule.is_empty() {
+        format!("\u{d7} {}", compact_hours_mins(args.options.target))
+    } else {
+        "of the schedule".to_owned()
+    };
+    let (through, worked, until_yesterday) = match args.options.overtime_through {
+        OvertimeThrough::Yesterday => (
+            "yesterday",
+            summary.total_worked - summary.worked_today,
+            "overtime",
+        ),
+        OvertimeThrough::Today => ("today", summary.total_worked, "overtime per yesterday"),
+    };
+    let vacation = summary.vacation(&args.options);
+    let vacation_today = summary
+        .vacations
+        .get(&summary.today)
+        .map_or(Duration::ZERO, |hours| min(*hours, target));
+    let term = |name: &str, hours: Duration| {
+        if hours.is_zero() {
+            String::new()
+        } else {
+            format!(" {} ({})", name, compact_hours_mins(hours))
+        }
+    };
+    format!(
+        "Total up to {} ({}) minus expected ({} days {} = {}){} = overtime {}\n\
+         Target ({}){} minus worked today ({}) minus {} ({}) = still to work {}\n",
+        through,
+        compact_hours_mins(worked),
+        expected_days,
+        per_day,
+        compact_hours_mins(expected),
+        term("plus vacation", vacation),
+        compact_hours_mins(summary.overtime),
+        compact_hours_mins(target),
+        term("minus vacation today", vacation_today),
+        compact_hours_mins(summary.worked_today),
+        until_yesterday,
+        compact_hours_mins(target - vacation_today - summary.worked_today - summary.still_to_work),
+        compact_hours_mins(summary.still_to_work),
+    )
+}
+
+/// The complete text output for `summary`: the reminder banner, the summary
+/// table, the punctuality line and the projects table.
+pub fn text(summary: &Summary, args: &Args, now: &PrimitiveDateTime) -> Result<String> {
+    let mut text = String::new();
+    if !args.no_reminders && summary.no_entries_today(now) {
+        let _ = writeln!(
+            text,
+            "{:!^width$}",
+            " No entries yet today ",
+            width = args.width
+        );
+    }
+    text.push('\n');
+    text.push_str(&render(&summary_rows(summary, args)?, args.width));
+    if let Some(expected_start) = args.expected_start {
+        let punctuality = summary.punctuality(expected_start, args.start_grace);
+        let _ = writeln!(
+            text,
+            "On-time days: {}, late days: {}, avg lateness: {}m",
+            punctuality.on_time_days,
+            punctuality.late_days,
+            punctuality
+                .avg_lateness
+                .map_or(0, |lateness| lateness.whole_minutes())
+        );
+    }
+    if args.streaks {
+        let _ = writeln!(
+            text,
+            "Current streak: {} days, longest streak: {} days",
+            summary.current_streak, summary.longest_streak
+        );
+    }
+    if let Some(due) = args
+        .break_after
+        .and_then(|limit| summary.break_due(now, limit))
+    {
+        let in_minutes = if due.is_zero() {
+            "now".to_owned()
+        } else {
+            // Rounded up, the last seconds read as 1 minute rather than 0.
+            format!("{} minutes", (due.whole_seconds() + 59) / 60)
+        };
+        let _ = writeln!(text, "Break recommended in: {}", in_minutes);
+    }
+    if let Some(path) = &args.holidays {
+        let off_days = summary.off_days_worked(&read_dates(path)?);
+        let _ = writeln!(
+            text,
+            "Weekend days worked: {}, holidays worked: {}",
+            off_days.saturdays + off_days.sundays,
+            off_days.holidays
+        );
+    }
+    let today = summary.today;
+    if args.days {
+        text.push_str(&render(&day_rows(summary, args, today)?, args.width));
+    }
+    if args.years {
+        text.push_str(&render(&year_rows(summary, args), args.width));
+    }
+    if args.part_of_day {
+        text.push_str(&render(&part_of_day_rows(summary, args), args.width));
+    }
+    if args.projects {
+        text.push_str(&render(&project_rows(summary, args, today), args.width));
+    }
+    if let Some(pattern) = &args.group_by {
+        text.push_str(&render(&group_rows(summary, args, pattern), args.width));
+    }
+    if args.explain {
+        text.push_str(&explain(summary, args));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib::{hours_mins, open_session, summarize_reader, NegativeStyle, Options};
+    use std::{collections::BTreeMap, io::Cursor};
+    use time::macros::datetime;
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row::Rule,
+            Row::field("Worked today:", "8 hours"),
+            Row::field("Time to leave:", "17:00:00"),
+            Row::Rule,
+        ]
+    }
+
+    #[test]
+    fn render_should_keep_default_layout() {
+        let table = render(&rows(), DEFAULT_WIDTH);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0].chars().count(), 71);
+        assert_eq!(lines[1], format!("{:<45}8 hours", "Worked today:"));
+    }
+
+    #[test]
+    fn render_should_scale_label_column_with_width() {
+        assert_eq!(
+            render(&rows(), 40),
+            "────────────────────────────────────────
+Worked today:            8 hours
+Time to leave:           17:00:00
+────────────────────────────────────────
+"
+        );
+    }
+
+    #[test]
+    fn render_should_cut_long_labels_short() {
+        let rows = vec![
+            Row::field("client:acme:a-very-long-project-name:design", "8 hours"),
+            Row::field("client:acme", "1 hours"),
+        ];
+        assert_eq!(
+            render(&rows, 40),
+            "client:acme:a-very-long\u{2026} 8 hours\n\
+             client:acme              1 hours\n"
+        );
+    }
+
+    #[test]
+    fn duration_should_apply_negative_style_to_grouped_hours() {
+        let args = Args {
+            group_digits: true,
+            negative_style: NegativeStyle::Parentheses,
+            ..Args::default()
+        };
+        assert_eq!(
+            duration(&args, -Duration::hours(1205)),
+            "(1,205 hours, 0     minutes)"
+        );
+    }
+
+    const DAY: Date = time::macros::date!(2022 - 01 - 05);
+
+    #[test]
+    fn duration_in_days_should_count_target_workdays() {
+        let mut args = Args::default();
+        let worked = Duration::hours(80);
+        assert_eq!(duration_in_days(&args, DAY, worked), hours_mins(worked));
+        args.in_days = true;
+        assert_eq!(
+            duration_in_days(&args, DAY, worked),
+            format!("{} (10.0 days)", hours_mins(worked))
+        );
+        args.options.target = Duration::hours(6);
+        assert!(duration_in_days(&args, DAY, Duration::hours(-3)).ends_with("(-0.5 days)"));
+        args.options.schedule = BTreeMap::from([(DAY, Duration::hours(4))]);
+        assert!(duration_in_days(&args, DAY, Duration::hours(-3)).ends_with("(-0.8 days)"));
+    }
+
+    #[test]
+    fn day_rows_should_label_recent_days() {
+        let log = "i 2021/12/20 09:00:00 acme\no 2021/12/20 17:00:00\n\
+                   i 2022/01/04 08:30:00 acme\no 2022/01/04 12:00:00\n\
+                   i 2022/01/05 09:00:00 acme\no 2022/01/05 10:00:00\n";
+        let now = datetime!(2022-01-05 11:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        let rows = day_rows(&summary, &Args::default(), now.date()).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                Row::field(
+                    "2021-12-20",
+                    format!("09:00:00  {}", hours_mins(Duration::hours(8)))
+                ),
+                Row::field(
+                    "Yesterday",
+                    format!("08:30:00  {}", hours_mins(Duration::minutes(210)))
+                ),
+                Row::field(
+                    "Today",
+                    format!("09:00:00  {}", hours_mins(Duration::hours(1)))
+                ),
+                Row::Rule,
+            ]
+        );
+    }
+
+    #[test]
+    fn text_should_label_days_relative_to_the_given_today() {
+        let log = "i 2022/01/03 08:30:00 acme\no 2022/01/03 12:00:00\n\
+                   i 2022/01/04 09:00:00 acme\no 2022/01/04 10:00:00\n";
+        let now = datetime!(2022-01-05 11:00:00);
+        let mut args = Args {
+            days: true,
+            projects: true,
+            no_reminders: true,
+            ..Args::default()
+        };
+        args.options.today = Some(time::macros::date!(2022 - 01 - 04));
+        let summary = summarize_reader(Cursor::new(log), &now, &args.options).unwrap();
+        let text = text(&summary, &args, &now).unwrap();
+        assert!(text.contains(&format!("{:<45}08:30:00", "Yesterday")));
+        assert!(text.contains(&format!("{:<45}09:00:00", "Today")));
+        assert!(text.contains("last worked 2022-01-04 (today)"));
+    }
+
+    #[test]
+    fn project_rows_should_show_last_worked_date() {
+        let log = "i 2022/01/03 09:00:00 client:acme\no 2022/01/03 17:00:00\n\
+                   i 2022/01/04 09:00:00 client:acme\no 2022/01/04 10:00:00\n";
+        let now = datetime!(2022-02-25 10:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(
+            project_rows(&summary, &Args::default(), now.date()),
+            vec![
+                Row::field(
+                    "client:acme",
+                    format!(
+                        "{} \u{2014} last worked 2022-01-04 (52 days ago)",
+                        hours_mins(Duration::hours(9))
+                    )
+                ),
+                Row::Rule,
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_should_show_the_intermediate_figures() {
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 18:00:00\n\
+                   i 2022/01/04 09:00:00 acme\no 2022/01/04 17:30:00\n\
+                   i 2022/01/05 09:00:00 acme\n";
+        let now = datetime!(2022-01-05 11:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(
+            explain(&summary, &Args::default()),
+            "Total up to yesterday (17h 30m) minus expected (2 days \u{d7} 8h 0m = 16h 0m) \
+             = overtime 1h 30m\n\
+             Target (8h 0m) minus worked today (2h 0m) minus overtime (1h 30m) \
+             = still to work 4h 30m\n"
+        );
+    }
+
+    #[test]
+    fn explain_should_show_the_vacation() {
+        let log = "# vacation 2022/01/04 4h\n\
+                   i 2022/01/03 09:00:00 acme\no 2022/01/03 18:00:00\n\
+                   i 2022/01/04 09:00:00 acme\no 2022/01/04 13:00:00\n\
+                   # vacation 2022/01/05 2h\n\
+                   i 2022/01/05 09:00:00 acme\n";
+        let now = datetime!(2022-01-05 11:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(
+            explain(&summary, &Args::default()),
+            "Total up to yesterday (13h 0m) minus expected (2 days \u{d7} 8h 0m = 16h 0m) \
+             plus vacation (4h 0m) = overtime 1h 0m\n\
+             Target (8h 0m) minus vacation today (2h 0m) minus worked today (2h 0m) \
+             minus overtime (1h 0m) = still to work 3h 0m\n"
+        );
+    }
+
+    #[test]
+    fn explain_should_follow_the_schedule() {
+        let log = "i 2022/05/30 09:00:00 acme\no 2022/05/30 17:00:00\n\
+                   i 2022/05/31 09:00:00 acme\no 2022/05/31 17:00:00\n\
+                   i 2022/06/01 09:00:00 acme\no 2022/06/01 17:00:00\n\
+                   i 2022/06/02 09:00:00 acme\n";
+        let now = datetime!(2022-06-02 11:00:00);
+        let options = Options {
+            schedule: BTreeMap::from([(time::macros::date!(2022 - 06 - 01), Duration::hours(6))]),
+            ..Options::default()
+        };
+        let summary = summarize_reader(Cursor::new(log), &now, &options).unwrap();
+        let args = Args {
+            options,
+            ..Args::default()
+        };
+        assert_eq!(
+            explain(&summary, &args),
+            "Total up to yesterday (24h 0m) minus expected (3 days of the schedule = 22h 0m) \
+             = overtime 2h 0m\n\
+             Target (6h 0m) minus worked today (2h 0m) minus overtime (2h 0m) \
+             = still to work 2h 0m\n"
+        );
+    }
+
+    #[test]
+    fn summary_rows_should_follow_sections() {
+        let log = "i 2022/01/04 09:00:00 acme\n";
+        let now = datetime!(2022-01-04 11:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        let args = Args {
+            sections: Some(vec![
+                "leave".to_owned(),
+                "worked-today".to_owned(),
+                "first-in".to_owned(),
+            ]),
+            ..Args::default()
+        };
+        assert_eq!(
+            summary_rows(&summary, &args).unwrap(),
+            vec![
+                Row::field("Time to leave:", "17:00"),
+                Row::field("Worked today:", hours_mins(Duration::hours(2))),
+                Row::field("First punch in today:", "09:00:00"),
+            ]
+        );
+        let rows = summary_rows(&summary, &Args::default()).unwrap();
+        assert!(rows.contains(&Row::field(
+            "Still to work this week:",
+            hours_mins(Duration::hours(14))
+        )));
+    }
+
+    #[test]
+    fn leave_should_be_shown_in_whole_minutes() {
+        let now = datetime!(2022-01-03 10:26:13);
+        let log = "i 2022/01/03 09:00:47 acme\n";
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(summary.time_to_leave, Some(datetime!(2022-01-03 17:00:47)));
+        let args = Args::default();
+        assert_eq!(
+            section(&summary, &args, "leave").unwrap(),
+            Some(Row::field("Time to leave:", "17:00"))
+        );
+        assert_eq!(
+            truncate_to_minute(datetime!(2022-01-03 17:33:47.5)),
+            datetime!(2022-01-03 17:33:00)
+        );
+    }
+
+    #[test]
+    fn status_rows_should_show_open_session_only() {
+        let log = "i 2022/01/04 09:00:00 acme\no 2022/01/04 12:00:00\n\
+                   i 2022/01/04 12:30:00 acme:dev\n";
+        let now = datetime!(2022-01-04 14:00:00);
+        let open = open_session(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(
+            status_rows(open.as_ref(), &Args::default()).unwrap(),
+            vec![
+                Row::field("Clocked in:", "yes"),
+                Row::field("Account:", "acme:dev"),
+                Row::field("Since:", "12:30:00  2022/01/04"),
+                Row::field("Elapsed:", hours_mins(Duration::minutes(90))),
+            ]
+        );
+
+        let log = "i 2022/01/04 09:00:00 acme\no 2022/01/04 12:00:00\n";
+        let open = open_session(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(open, None);
+        assert_eq!(
+            status_rows(None, &Args::default()).unwrap(),
+            vec![Row::field("Clocked in:", "no")]
+        );
+    }
+
+    #[test]
+    fn since_last_clockin_should_show_elapsed_time_only_when_clocked_in() {
+        let log = "i 2022/01/04 09:00:00 acme\no 2022/01/04 12:00:00\n\
+                   i 2022/01/04 12:30:00 acme\n";
+        let now = datetime!(2022-01-04 13:35:00);
+        let open = open_session(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(since_last_clockin(open.as_ref()), "1h05m\n");
+        assert_eq!(since_last_clockin(None), CLOCKED_OUT);
+    }
+
+    #[test]
+    fn oneline_should_show_time_to_leave_only_when_clocked_in() {
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 17:00:00\n\
+                   i 2022/01/04 08:00:00 acme\no 2022/01/04 12:00:00\n\
+                   i 2022/01/04 12:30:00 acme\n";
+        let now = datetime!(2022-01-04 14:42:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(
+            oneline(&summary, &Args::default()).unwrap(),
+            "\u{25b6} 6h12m | bal -1h48m | leave 16:30\n"
+        );
+
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 17:00:00\n\
+                   i 2022/01/04 08:00:00 acme\no 2022/01/04 16:12:00\n";
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(
+            oneline(&summary, &Args::default()).unwrap(),
+            "\u{23f8} 8h12m | bal +0h12m\n"
+        );
+    }
+
+    #[test]
+    fn oneline_should_show_banked_overtime_before_clocking_in() {
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 18:00:00\n\
+                   i 2022/01/04 09:00:00 acme\no 2022/01/04 19:00:00\n";
+        let now = datetime!(2022-01-05 08:00:00);
+        let mut args = Args::default();
+        args.options.exempt_first_day = false;
+        let summary = summarize_reader(Cursor::new(log), &now, &args.options).unwrap();
+        assert_eq!(
+            oneline(&summary, &args).unwrap(),
+            "\u{23f8} 0h00m | bal +3h00m\n"
+        );
+    }
+
+    #[test]
+    fn year_rows_should_label_fiscal_years() {
+        let log = "i 2023/03/31 09:00:00 acme\no 2023/03/31 17:00:00\n\
+                   i 2023/04/03 09:00:00 acme\no 2023/04/03 13:00:00\n";
+        let now = datetime!(2023-04-04 12:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        let args = Args {
+            fiscal_year_start: Month::April,
+            ..Args::default()
+        };
+        assert_eq!(
+            year_rows(&summary, &args),
+            vec![
+                Row::field("2022/23", hours_mins(Duration::hours(8))),
+                Row::field("2023/24", hours_mins(Duration::hours(4))),
+                Row::Rule,
+            ]
+        );
+    }
+
+    #[test]
+    fn timesheet_should_render_the_week() {
+        let log = "i 2022/01/03 09:00:00 acme\no 2022/01/03 12:00:00\n\
+                   i 2022/01/03 12:30:00 acme\no 2022/01/03 17:30:00\n\
+                   i 2022/01/05 08:15:00 beta\no 2022/01/05 16:20:00\n\
+                   i 2022/01/10 09:00:00 acme\no 2022/01/10 10:00:00\n";
+        let now = datetime!(2022-01-10 11:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        assert_eq!(
+            timesheet(&summary, time::macros::date!(2022 - 01 - 06)).unwrap(),
+            "Timesheet 2022-01-03 - 2022-01-09
+Day  Date        In     Out     Total
+Mon  2022-01-03  09:00  17:30    8:00
+Tue  2022-01-04
+Wed  2022-01-05  08:15  16:20    8:05
+Thu  2022-01-06
+Fri  2022-01-07
+Sat  2022-01-08
+Sun  2022-01-09
+Total                           16:05
+"
+        );
+    }
+
+    #[test]
+    fn balance_chart_should_end_at_overtime() {
+        let log = "i 2022/04/18 09:00:00 acme\no 2022/04/18 17:30:00\n\
+                   i 2022/04/19 09:00:00 acme\no 2022/04/19 16:00:00\n\
+                   i 2022/04/20 09:00:00 acme\no 2022/04/20 17:15:00\n";
+        let now = datetime!(2022-04-21 09:00:00);
+        let summary = summarize_reader(Cursor::new(log), &now, &Options::default()).unwrap();
+        // The first day is exempt from the target by default.
+        assert_eq!(
+            balance_chart(&summary, &Args::default()),
+            "2022-04-18  +8h30m  (cum +8h30m)\n\
+             2022-04-19  -1h00m  (cum +7h30m)\n\
+             2022-04-20  +0h15m  (cum +7h45m)\n"
+        );
+        let days = summary.daily_balance(&Options::default());
+        assert_eq!(days.last().unwrap().balance, summary.overtime);
+
+        let options = Options {
+            exempt_first_day: false,
+            ..Options::default()
+        };
+        let summary = summarize_reader(Cursor::new(log), &now, &options).unwrap();
+        let days = summary.daily_balance(&options);
+        assert_eq!(days[0].deviation, Duration::minutes(30));
+        assert_eq!(days.last().unwrap().balance, summary.overtime);
+    }
+
+    #[test]
+    fn text_should_reflect_entries_appended_between_runs() {
+        let args = Args {
+            no_reminders: true,
+            projects: true,
+            ..Args::default()
+        };
+        let mut log = "i 2022/01/03 09:00:00 acme\n".to_owned();
+        let summarize = |log: &str, now| {
+            summarize_reader(Cursor::new(log.to_owned()), &now, &Options::default()).unwrap()
+        };
+        let now = datetime!(2022-01-03 10:00:00);
+        let text = text(&summarize(&log, now), &args, &now).unwrap();
+        assert!(text.contains(&format!(
+            "{:<45}{}",
+            "Worked today:",
+            hours_mins(Duration::hours(1))
+        )));
+
+        log.push_str("o 2022/01/03 11:30:00\n");
+        let now = datetime!(2022-01-03 12:00:00);
+        let text = super::text(&summarize(&log, now), &args, &now).unwrap();
+        let worked = hours_mins(Duration::minutes(150));
+        assert!(text.contains(&format!("{:<45}{}", "Worked today:", worked)));
+        assert!(text.contains(&format!("{:<45}{}", "acme", worked)));
+    }
+}